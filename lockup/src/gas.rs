@@ -60,6 +60,42 @@ pub mod owner_callbacks {
     /// Requires BASE for local execution.
     pub const ON_WHITELIST_IS_WHITELISTED: u64 = super::BASE_GAS;
 
+    /// Gas attached to the inner callback for processing whitelist check results before the
+    /// deposit to the staking pool.
+    /// Requires BASE for local execution + gas for deposit + gas for another callback.
+    pub const ON_WHITELIST_IS_WHITELISTED_TO_DEPOSIT: u64 =
+        super::BASE_GAS + super::staking_pool::DEPOSIT + ON_STAKING_POOL_DEPOSIT;
+
+    /// Gas attached to the inner callback for processing whitelist check results before the
+    /// deposit and stake to the staking pool.
+    /// Requires BASE for local execution + gas for deposit and stake + gas for another callback.
+    pub const ON_WHITELIST_IS_WHITELISTED_TO_DEPOSIT_AND_STAKE: u64 = super::BASE_GAS
+        + super::staking_pool::DEPOSIT_AND_STAKE
+        + ON_STAKING_POOL_DEPOSIT_AND_STAKE;
+
+    /// Gas attached to the inner callback for processing whitelist check results before staking
+    /// at the staking pool.
+    /// Requires BASE for local execution + gas for stake + gas for another callback.
+    pub const ON_WHITELIST_IS_WHITELISTED_TO_STAKE: u64 =
+        super::BASE_GAS + super::staking_pool::STAKE + ON_STAKING_POOL_STAKE;
+
+    /// Gas attached to the inner callback for processing whitelist check results to exit the
+    /// de-listed staking pool.
+    /// Requires BASE for local execution + gas for getting the staked balance + gas for another
+    /// callback.
+    pub const ON_WHITELIST_IS_WHITELISTED_TO_EXIT: u64 = super::BASE_GAS
+        + super::staking_pool::GET_ACCOUNT_STAKED_BALANCE
+        + ON_GET_ACCOUNT_STAKED_BALANCE_TO_EXIT;
+
+    /// Gas attached to the inner callback for processing result of the call to get the current
+    /// staked balance to exit the de-listed staking pool.
+    /// The callback either unstakes or proceeds with withdrawing. Withdrawing requires more gas.
+    /// Requires BASE for local updates + gas for getting the unstaked balance + gas for another
+    /// callback.
+    pub const ON_GET_ACCOUNT_STAKED_BALANCE_TO_EXIT: u64 = super::BASE_GAS
+        + super::staking_pool::GET_ACCOUNT_UNSTAKED_BALANCE
+        + ON_GET_ACCOUNT_UNSTAKED_BALANCE_TO_WITHDRAW_BY_OWNER;
+
     /// Gas attached to the inner callback for processing result of the deposit call to the
     /// staking pool.
     /// Requires BASE for local updates.
@@ -1,4 +1,5 @@
 use crate::*;
+use near_sdk::{Promise, PromiseResult};
 
 /********************/
 /* Internal methods */
@@ -20,6 +21,41 @@ impl LockupContract {
             .status = status;
     }
 
+    /// Checks the currently selected staking pool against the whitelist contract.
+    pub fn check_staking_pool_is_whitelisted(&self) -> Promise {
+        ext_whitelist::is_whitelisted(
+            self.staking_information
+                .as_ref()
+                .expect("Staking pool should be selected")
+                .staking_pool_account_id
+                .clone(),
+            &self.staking_pool_whitelist_account_id,
+            NO_DEPOSIT,
+            gas::whitelist::IS_WHITELISTED,
+        )
+    }
+
+    /// Returns `true` if the whitelist check of the selected staking pool succeeded. Otherwise
+    /// cancels the given operation and makes the staking pool idle again.
+    pub fn internal_staking_pool_is_still_whitelisted(&mut self, operation: &str) -> bool {
+        if whitelist_check_result().unwrap_or(false) {
+            return true;
+        }
+        env::log(
+            format!(
+                "The {} at @{} was cancelled, because the staking pool is not whitelisted",
+                operation,
+                self.staking_information
+                    .as_ref()
+                    .unwrap()
+                    .staking_pool_account_id
+            )
+            .as_bytes(),
+        );
+        self.set_staking_pool_status(TransactionStatus::Idle);
+        false
+    }
+
     pub fn set_termination_status(&mut self, status: TerminationStatus) {
         if let VestingInformation::Terminating(termination_information) =
             &mut self.vesting_information
@@ -127,3 +163,23 @@ impl LockupContract {
         )
     }
 }
+
+/// Returns the result of the whitelist check from the promise result, or `None` if the call to
+/// the whitelist contract has failed.
+pub fn whitelist_check_result() -> Option<bool> {
+    assert_eq!(env::promise_results_count(), 1, "Expected 1 promise result");
+    match env::promise_result(0) {
+        PromiseResult::Successful(value) => near_sdk::serde_json::from_slice(&value).ok(),
+        _ => None,
+    }
+}
+
+/// Returns the balance from the promise result of the staking pool view call, or `None` if the
+/// call to the staking pool has failed.
+pub fn staking_pool_balance_result() -> Option<WrappedBalance> {
+    assert_eq!(env::promise_results_count(), 1, "Expected 1 promise result");
+    match env::promise_result(0) {
+        PromiseResult::Successful(value) => near_sdk::serde_json::from_slice(&value).ok(),
+        _ => None,
+    }
+}
@@ -71,6 +71,19 @@ pub trait ExtLockupContractOwner {
         staking_pool_account_id: AccountId,
     ) -> bool;
 
+    fn on_whitelist_is_whitelisted_to_deposit(&mut self, amount: WrappedBalance) -> bool;
+
+    fn on_whitelist_is_whitelisted_to_deposit_and_stake(
+        &mut self,
+        amount: WrappedBalance,
+    ) -> bool;
+
+    fn on_whitelist_is_whitelisted_to_stake(&mut self, amount: WrappedBalance) -> bool;
+
+    fn on_whitelist_is_whitelisted_to_exit(&mut self) -> bool;
+
+    fn on_get_account_staked_balance_to_exit(&mut self) -> PromiseOrValue<bool>;
+
     fn on_staking_pool_deposit(&mut self, amount: WrappedBalance) -> bool;
 
     fn on_staking_pool_deposit_and_stake(&mut self, amount: WrappedBalance) -> bool;
@@ -248,7 +261,7 @@ impl LockupContract {
 mod tests {
     use std::convert::TryInto;
 
-    use near_sdk::{testing_env, MockedBlockchain, PromiseResult, VMContext};
+    use near_sdk::{testing_env, MockedBlockchain, PromiseOrValue, PromiseResult, VMContext};
 
     use test_utils::*;
 
@@ -684,6 +697,13 @@ mod tests {
         context.predecessor_account_id = account_owner();
         testing_env!(context.clone());
         contract.deposit_to_staking_pool(amount.into());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_deposit(amount.into());
         context.account_balance = env::account_balance();
         assert_eq!(context.account_balance, to_yocto(LOCKUP_NEAR) - amount);
 
@@ -700,6 +720,13 @@ mod tests {
         testing_env!(context.clone());
         contract.stake(amount.into());
 
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_stake(amount.into());
+
         context.predecessor_account_id = lockup_account();
         testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
         contract.on_staking_pool_stake(amount.into());
@@ -759,6 +786,13 @@ mod tests {
         context.predecessor_account_id = account_owner();
         testing_env!(context.clone());
         contract.deposit_to_staking_pool(amount.into());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_deposit(amount.into());
         context.account_balance = env::account_balance();
         assert_eq!(context.account_balance, to_yocto(LOCKUP_NEAR) - amount);
 
@@ -771,6 +805,13 @@ mod tests {
         testing_env!(context.clone());
         contract.stake(amount.into());
 
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_stake(amount.into());
+
         context.predecessor_account_id = lockup_account();
         testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
         contract.on_staking_pool_stake(amount.into());
@@ -863,6 +904,190 @@ mod tests {
         contract.on_whitelist_is_whitelisted(false, staking_pool.clone());
     }
 
+    #[test]
+    fn test_staking_pool_delisted_before_deposit() {
+        let (mut context, mut contract) = lockup_only_setup();
+        context.predecessor_account_id = account_owner();
+        context.signer_account_id = account_owner();
+        context.signer_account_pk = public_key(2).try_into().unwrap();
+
+        // Selecting staking pool
+        let staking_pool = "staking_pool".to_string();
+        testing_env!(context.clone());
+        contract.select_staking_pool(staking_pool.clone());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted(true, staking_pool.clone());
+
+        // The staking pool was removed from the whitelist before the deposit.
+        let amount = to_yocto(LOCKUP_NEAR - 100);
+        context.predecessor_account_id = account_owner();
+        testing_env!(context.clone());
+        contract.deposit_to_staking_pool(amount.into());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"false".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_deposit(amount.into());
+        assert_eq!(env::account_balance(), to_yocto(LOCKUP_NEAR));
+
+        // The failed whitelist check also cancels the deposit.
+        context.predecessor_account_id = account_owner();
+        testing_env!(context.clone());
+        contract.deposit_and_stake(amount.into());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
+        contract.on_whitelist_is_whitelisted_to_deposit_and_stake(amount.into());
+        assert_eq!(env::account_balance(), to_yocto(LOCKUP_NEAR));
+
+        // The staking pool is idle again, so it can be unselected.
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert_eq!(contract.get_known_deposited_balance().0, 0);
+        context.is_view = false;
+
+        context.predecessor_account_id = account_owner();
+        testing_env!(context.clone());
+        contract.unselect_staking_pool();
+        assert_eq!(contract.get_staking_pool_account_id(), None);
+    }
+
+    #[test]
+    fn test_exit_delisted_pool() {
+        let (mut context, mut contract) = lockup_only_setup();
+        context.predecessor_account_id = account_owner();
+        context.signer_account_id = account_owner();
+        context.signer_account_pk = public_key(2).try_into().unwrap();
+
+        // Selecting staking pool
+        let staking_pool = "staking_pool".to_string();
+        testing_env!(context.clone());
+        contract.select_staking_pool(staking_pool.clone());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted(true, staking_pool.clone());
+
+        // Anyone can try to exit, but the staking pool is still whitelisted.
+        context.predecessor_account_id = non_owner();
+        testing_env!(context.clone());
+        contract.exit_delisted_pool();
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        match contract.on_whitelist_is_whitelisted_to_exit() {
+            PromiseOrValue::Value(exited) => assert!(!exited),
+            PromiseOrValue::Promise(_) => panic!("Expected to stay in the staking pool"),
+        };
+
+        // The staking pool was removed from the whitelist. Unstaking everything.
+        let staked_amount = to_yocto(100);
+        context.predecessor_account_id = non_owner();
+        testing_env!(context.clone());
+        contract.exit_delisted_pool();
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"false".to_vec()),
+        );
+        match contract.on_whitelist_is_whitelisted_to_exit() {
+            PromiseOrValue::Value(_) => panic!("Expected to exit the staking pool"),
+            PromiseOrValue::Promise(_) => (),
+        };
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(format!("\"{}\"", staked_amount).into_bytes()),
+        );
+        match contract.on_get_account_staked_balance_to_exit() {
+            PromiseOrValue::Value(_) => panic!("Expected to unstake"),
+            PromiseOrValue::Promise(_) => (),
+        };
+        testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
+        contract.on_staking_pool_unstake(staked_amount.into());
+
+        // Withdrawing after nothing is staked anymore.
+        context.predecessor_account_id = non_owner();
+        testing_env!(context.clone());
+        contract.exit_delisted_pool();
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"false".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_exit();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"\"0\"".to_vec()),
+        );
+        contract.on_get_account_staked_balance_to_exit();
+        testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
+        contract.on_get_account_unstaked_balance_to_withdraw_by_owner(staked_amount.into());
+        testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
+        contract.on_staking_pool_withdraw(staked_amount.into());
+
+        // The staking pool is idle again.
+        context.predecessor_account_id = account_owner();
+        testing_env!(context.clone());
+        contract.refresh_staking_pool_balance();
+    }
+
+    #[test]
+    fn test_exit_delisted_pool_failed_staked_balance() {
+        let (mut context, mut contract) = lockup_only_setup();
+        context.predecessor_account_id = account_owner();
+        context.signer_account_id = account_owner();
+        context.signer_account_pk = public_key(2).try_into().unwrap();
+
+        // Selecting staking pool
+        let staking_pool = "staking_pool".to_string();
+        testing_env!(context.clone());
+        contract.select_staking_pool(staking_pool.clone());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted(true, staking_pool.clone());
+
+        context.predecessor_account_id = non_owner();
+        testing_env!(context.clone());
+        contract.exit_delisted_pool();
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"false".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_exit();
+
+        // The staking pool call has failed, the lockup is released to retry.
+        testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
+        match contract.on_get_account_staked_balance_to_exit() {
+            PromiseOrValue::Value(exited) => assert!(!exited),
+            PromiseOrValue::Promise(_) => panic!("Expected to stop exiting"),
+        };
+
+        context.predecessor_account_id = non_owner();
+        testing_env!(context.clone());
+        contract.exit_delisted_pool();
+    }
+
     #[test]
     #[should_panic(expected = "Staking pool is not selected")]
     fn test_staking_pool_unselecting_non_selected() {
@@ -901,6 +1126,13 @@ mod tests {
         context.predecessor_account_id = account_owner();
         testing_env!(context.clone());
         contract.deposit_to_staking_pool(amount.into());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_deposit(amount.into());
         context.account_balance = env::account_balance();
 
         context.predecessor_account_id = lockup_account();
@@ -947,6 +1179,13 @@ mod tests {
             context.predecessor_account_id = account_owner();
             testing_env!(context.clone());
             contract.deposit_to_staking_pool(amount.into());
+
+            context.predecessor_account_id = lockup_account();
+            testing_env_with_promise_results(
+                context.clone(),
+                PromiseResult::Successful(b"true".to_vec()),
+            );
+            contract.on_whitelist_is_whitelisted_to_deposit(amount.into());
             context.account_balance = env::account_balance();
             assert_eq!(context.account_balance, lockup_amount - total_amount);
 
@@ -1771,6 +2010,13 @@ mod tests {
         context.predecessor_account_id = account_owner();
         testing_env!(context.clone());
         contract.deposit_to_staking_pool(stake_amount.into());
+
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_deposit(stake_amount.into());
         context.account_balance = env::account_balance();
 
         context.predecessor_account_id = lockup_account();
@@ -1782,6 +2028,13 @@ mod tests {
         testing_env!(context.clone());
         contract.stake(stake_amount.into());
 
+        context.predecessor_account_id = lockup_account();
+        testing_env_with_promise_results(
+            context.clone(),
+            PromiseResult::Successful(b"true".to_vec()),
+        );
+        contract.on_whitelist_is_whitelisted_to_stake(stake_amount.into());
+
         context.predecessor_account_id = lockup_account();
         testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
         contract.on_staking_pool_stake(stake_amount.into());
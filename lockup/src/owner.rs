@@ -75,9 +75,11 @@ impl LockupContract {
 
     /// OWNER'S METHOD
     ///
-    /// Requires 100 TGas (4 * BASE_GAS)
+    /// Requires 150 TGas (6 * BASE_GAS)
     ///
-    /// Deposits the given extra amount to the staking pool
+    /// Deposits the given extra amount to the staking pool.
+    /// The staking pool is checked against the whitelist first, the deposit is cancelled if it's
+    /// no longer whitelisted.
     pub fn deposit_to_staking_pool(&mut self, amount: WrappedBalance) -> Promise {
         self.assert_owner();
         assert!(amount.0 > 0, "Amount should be positive");
@@ -102,28 +104,23 @@ impl LockupContract {
 
         self.set_staking_pool_status(TransactionStatus::Busy);
 
-        ext_staking_pool::deposit(
-            &self
-                .staking_information
-                .as_ref()
-                .unwrap()
-                .staking_pool_account_id,
-            amount.0,
-            gas::staking_pool::DEPOSIT,
+        self.check_staking_pool_is_whitelisted().then(
+            ext_self_owner::on_whitelist_is_whitelisted_to_deposit(
+                amount,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                gas::owner_callbacks::ON_WHITELIST_IS_WHITELISTED_TO_DEPOSIT,
+            ),
         )
-        .then(ext_self_owner::on_staking_pool_deposit(
-            amount,
-            &env::current_account_id(),
-            NO_DEPOSIT,
-            gas::owner_callbacks::ON_STAKING_POOL_DEPOSIT,
-        ))
     }
 
     /// OWNER'S METHOD
     ///
-    /// Requires 125 TGas (5 * BASE_GAS)
+    /// Requires 175 TGas (7 * BASE_GAS)
     ///
-    /// Deposits and stakes the given extra amount to the selected staking pool
+    /// Deposits and stakes the given extra amount to the selected staking pool.
+    /// The staking pool is checked against the whitelist first, the deposit is cancelled if it's
+    /// no longer whitelisted.
     pub fn deposit_and_stake(&mut self, amount: WrappedBalance) -> Promise {
         self.assert_owner();
         assert!(amount.0 > 0, "Amount should be positive");
@@ -148,21 +145,14 @@ impl LockupContract {
 
         self.set_staking_pool_status(TransactionStatus::Busy);
 
-        ext_staking_pool::deposit_and_stake(
-            &self
-                .staking_information
-                .as_ref()
-                .unwrap()
-                .staking_pool_account_id,
-            amount.0,
-            gas::staking_pool::DEPOSIT_AND_STAKE,
+        self.check_staking_pool_is_whitelisted().then(
+            ext_self_owner::on_whitelist_is_whitelisted_to_deposit_and_stake(
+                amount,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                gas::owner_callbacks::ON_WHITELIST_IS_WHITELISTED_TO_DEPOSIT_AND_STAKE,
+            ),
         )
-        .then(ext_self_owner::on_staking_pool_deposit_and_stake(
-            amount,
-            &env::current_account_id(),
-            NO_DEPOSIT,
-            gas::owner_callbacks::ON_STAKING_POOL_DEPOSIT_AND_STAKE,
-        ))
     }
 
     /// OWNER'S METHOD
@@ -295,9 +285,11 @@ impl LockupContract {
 
     /// OWNER'S METHOD
     ///
-    /// Requires 125 TGas (5 * BASE_GAS)
+    /// Requires 175 TGas (7 * BASE_GAS)
     ///
-    /// Stakes the given extra amount at the staking pool
+    /// Stakes the given extra amount at the staking pool.
+    /// The staking pool is checked against the whitelist first, the staking is cancelled if it's
+    /// no longer whitelisted.
     pub fn stake(&mut self, amount: WrappedBalance) -> Promise {
         self.assert_owner();
         assert!(amount.0 > 0, "Amount should be positive");
@@ -318,22 +310,14 @@ impl LockupContract {
 
         self.set_staking_pool_status(TransactionStatus::Busy);
 
-        ext_staking_pool::stake(
-            amount,
-            &self
-                .staking_information
-                .as_ref()
-                .unwrap()
-                .staking_pool_account_id,
-            NO_DEPOSIT,
-            gas::staking_pool::STAKE,
+        self.check_staking_pool_is_whitelisted().then(
+            ext_self_owner::on_whitelist_is_whitelisted_to_stake(
+                amount,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                gas::owner_callbacks::ON_WHITELIST_IS_WHITELISTED_TO_STAKE,
+            ),
         )
-        .then(ext_self_owner::on_staking_pool_stake(
-            amount,
-            &env::current_account_id(),
-            NO_DEPOSIT,
-            gas::owner_callbacks::ON_STAKING_POOL_STAKE,
-        ))
     }
 
     /// OWNER'S METHOD
@@ -418,6 +402,41 @@ impl LockupContract {
         ))
     }
 
+    /// ANYONE'S METHOD
+    ///
+    /// Requires 275 TGas (11 * BASE_GAS)
+    ///
+    /// Moves the funds out of the selected staking pool if it was removed from the whitelist.
+    /// If the pool is no longer whitelisted, unstakes all staked balance. If nothing is staked,
+    /// withdraws the unstaked balance instead. So the first call starts unstaking and the call
+    /// after the unstaked balance becomes available (4 epochs) withdraws it back to this account.
+    /// Does nothing if the staking pool is still whitelisted.
+    pub fn exit_delisted_pool(&mut self) -> Promise {
+        self.assert_staking_pool_is_idle();
+        self.assert_no_termination();
+
+        env::log(
+            format!(
+                "Checking whether the staking pool @{} is still whitelisted to exit it",
+                self.staking_information
+                    .as_ref()
+                    .unwrap()
+                    .staking_pool_account_id
+            )
+            .as_bytes(),
+        );
+
+        self.set_staking_pool_status(TransactionStatus::Busy);
+
+        self.check_staking_pool_is_whitelisted().then(
+            ext_self_owner::on_whitelist_is_whitelisted_to_exit(
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                gas::owner_callbacks::ON_WHITELIST_IS_WHITELISTED_TO_EXIT,
+            ),
+        )
+    }
+
     /// OWNER'S METHOD
     ///
     /// Requires 75 TGas (3 * BASE_GAS)
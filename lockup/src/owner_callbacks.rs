@@ -24,6 +24,216 @@ impl LockupContract {
         true
     }
 
+    /// Called after the selected staking pool was checked in the whitelist before the deposit.
+    /// Deposits the amount if the staking pool is still whitelisted.
+    pub fn on_whitelist_is_whitelisted_to_deposit(
+        &mut self,
+        amount: WrappedBalance,
+    ) -> PromiseOrValue<bool> {
+        assert_self();
+        if !self.internal_staking_pool_is_still_whitelisted("deposit") {
+            return PromiseOrValue::Value(false);
+        }
+
+        ext_staking_pool::deposit(
+            &self
+                .staking_information
+                .as_ref()
+                .unwrap()
+                .staking_pool_account_id,
+            amount.0,
+            gas::staking_pool::DEPOSIT,
+        )
+        .then(ext_self_owner::on_staking_pool_deposit(
+            amount,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            gas::owner_callbacks::ON_STAKING_POOL_DEPOSIT,
+        ))
+        .into()
+    }
+
+    /// Called after the selected staking pool was checked in the whitelist before the deposit and
+    /// stake. Deposits and stakes the amount if the staking pool is still whitelisted.
+    pub fn on_whitelist_is_whitelisted_to_deposit_and_stake(
+        &mut self,
+        amount: WrappedBalance,
+    ) -> PromiseOrValue<bool> {
+        assert_self();
+        if !self.internal_staking_pool_is_still_whitelisted("deposit and stake") {
+            return PromiseOrValue::Value(false);
+        }
+
+        ext_staking_pool::deposit_and_stake(
+            &self
+                .staking_information
+                .as_ref()
+                .unwrap()
+                .staking_pool_account_id,
+            amount.0,
+            gas::staking_pool::DEPOSIT_AND_STAKE,
+        )
+        .then(ext_self_owner::on_staking_pool_deposit_and_stake(
+            amount,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            gas::owner_callbacks::ON_STAKING_POOL_DEPOSIT_AND_STAKE,
+        ))
+        .into()
+    }
+
+    /// Called after the selected staking pool was checked in the whitelist before staking.
+    /// Stakes the amount if the staking pool is still whitelisted.
+    pub fn on_whitelist_is_whitelisted_to_stake(
+        &mut self,
+        amount: WrappedBalance,
+    ) -> PromiseOrValue<bool> {
+        assert_self();
+        if !self.internal_staking_pool_is_still_whitelisted("staking") {
+            return PromiseOrValue::Value(false);
+        }
+
+        ext_staking_pool::stake(
+            amount,
+            &self
+                .staking_information
+                .as_ref()
+                .unwrap()
+                .staking_pool_account_id,
+            NO_DEPOSIT,
+            gas::staking_pool::STAKE,
+        )
+        .then(ext_self_owner::on_staking_pool_stake(
+            amount,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            gas::owner_callbacks::ON_STAKING_POOL_STAKE,
+        ))
+        .into()
+    }
+
+    /// Called after the selected staking pool was checked in the whitelist to exit it.
+    /// If the staking pool is no longer whitelisted, fetches the staked balance to unstake it.
+    pub fn on_whitelist_is_whitelisted_to_exit(&mut self) -> PromiseOrValue<bool> {
+        assert_self();
+        // Failing to check the whitelist is not a reason to exit the staking pool.
+        if whitelist_check_result().unwrap_or(true) {
+            env::log(
+                format!(
+                    "The staking pool @{} is still whitelisted",
+                    self.staking_information
+                        .as_ref()
+                        .unwrap()
+                        .staking_pool_account_id
+                )
+                .as_bytes(),
+            );
+            self.set_staking_pool_status(TransactionStatus::Idle);
+            return PromiseOrValue::Value(false);
+        }
+
+        env::log(
+            format!(
+                "The staking pool @{} is no longer whitelisted. Exiting it",
+                self.staking_information
+                    .as_ref()
+                    .unwrap()
+                    .staking_pool_account_id
+            )
+            .as_bytes(),
+        );
+
+        ext_staking_pool::get_account_staked_balance(
+            env::current_account_id(),
+            &self
+                .staking_information
+                .as_ref()
+                .unwrap()
+                .staking_pool_account_id,
+            NO_DEPOSIT,
+            gas::staking_pool::GET_ACCOUNT_STAKED_BALANCE,
+        )
+        .then(ext_self_owner::on_get_account_staked_balance_to_exit(
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            gas::owner_callbacks::ON_GET_ACCOUNT_STAKED_BALANCE_TO_EXIT,
+        ))
+        .into()
+    }
+
+    /// Called after the request to get the current staked balance to exit the de-listed staking
+    /// pool. Unstakes the staked balance, or withdraws the unstaked balance if nothing is staked.
+    /// If the staking pool call has failed, releases the staking pool to allow retrying.
+    pub fn on_get_account_staked_balance_to_exit(&mut self) -> PromiseOrValue<bool> {
+        assert_self();
+        let staking_pool_account_id = self
+            .staking_information
+            .as_ref()
+            .unwrap()
+            .staking_pool_account_id
+            .clone();
+        let staked_balance = match staking_pool_balance_result() {
+            Some(staked_balance) => staked_balance,
+            None => {
+                env::log(
+                    format!(
+                        "Failed to get the staked balance from the staking pool @{}",
+                        staking_pool_account_id
+                    )
+                    .as_bytes(),
+                );
+                self.set_staking_pool_status(TransactionStatus::Idle);
+                return PromiseOrValue::Value(false);
+            }
+        };
+        if staked_balance.0 > 0 {
+            env::log(
+                format!(
+                    "Unstaking {} from the staking pool @{}",
+                    staked_balance.0, staking_pool_account_id
+                )
+                .as_bytes(),
+            );
+
+            ext_staking_pool::unstake(
+                staked_balance,
+                &staking_pool_account_id,
+                NO_DEPOSIT,
+                gas::staking_pool::UNSTAKE,
+            )
+            .then(ext_self_owner::on_staking_pool_unstake(
+                staked_balance,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                gas::owner_callbacks::ON_STAKING_POOL_UNSTAKE,
+            ))
+            .into()
+        } else {
+            env::log(
+                format!(
+                    "Going to query the unstaked balance at the staking pool @{}",
+                    staking_pool_account_id
+                )
+                .as_bytes(),
+            );
+
+            ext_staking_pool::get_account_unstaked_balance(
+                env::current_account_id(),
+                &staking_pool_account_id,
+                NO_DEPOSIT,
+                gas::staking_pool::GET_ACCOUNT_UNSTAKED_BALANCE,
+            )
+            .then(
+                ext_self_owner::on_get_account_unstaked_balance_to_withdraw_by_owner(
+                    &env::current_account_id(),
+                    NO_DEPOSIT,
+                    gas::owner_callbacks::ON_GET_ACCOUNT_UNSTAKED_BALANCE_TO_WITHDRAW_BY_OWNER,
+                ),
+            )
+            .into()
+        }
+    }
+
     /// Called after a deposit amount was transferred out of this account to the staking pool.
     /// This method needs to update staking pool status.
     pub fn on_staking_pool_deposit(&mut self, amount: WrappedBalance) -> bool {
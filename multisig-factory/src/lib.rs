@@ -1,41 +1,129 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::json_types::Base58PublicKey;
+use near_sdk::collections::{LookupMap, UnorderedSet};
+use near_sdk::json_types::{Base58PublicKey, Base64VecU8, U128};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::serde_json::json;
-use near_sdk::{env, near_bindgen, AccountId, Promise};
+use near_sdk::serde_json::{self, json};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Balance, Promise, PromiseOrValue};
+
+mod utils;
+use crate::utils::*;
 
 #[global_allocator]
 static ALLOC: near_sdk::wee_alloc::WeeAlloc<'_> = near_sdk::wee_alloc::WeeAlloc::INIT;
 
+/// Multisig code that is used when no other code version is set as default.
 const CODE: &[u8] = include_bytes!("../../multisig2/res/multisig2.wasm");
 
 /// This gas spent on the call & account creation, the rest goes to the `new` call.
 const CREATE_CALL_GAS: u64 = 50_000_000_000_000;
 
-#[derive(Serialize, Deserialize)]
+/// The amount of gas the contract will attach to the callback to itself.
+const ON_CREATE_CALL_GAS: u64 = 20_000_000_000_000;
+
+/// There is no deposit balance attached.
+const NO_DEPOSIT: Balance = 0;
+
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde", untagged)]
 pub enum MultisigMember {
     AccessKey { public_key: Base58PublicKey },
     Account { account_id: AccountId },
 }
 
+impl MultisigMember {
+    /// Key of the member in the index of multisigs by member.
+    fn to_key(&self) -> String {
+        serde_json::to_string(self).expect("Failed to serialize")
+    }
+}
+
 #[near_bindgen]
-#[derive(BorshSerialize, BorshDeserialize, Default)]
-pub struct MultisigFactory {}
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MultisigFactory {
+    /// Account that manages code versions of the multisig.
+    owner_id: AccountId,
+    /// Account IDs of the multisigs created by this factory.
+    multisig_account_ids: UnorderedSet<AccountId>,
+    /// Account IDs of the created multisigs per initial member.
+    multisigs_by_member: LookupMap<String, Vec<AccountId>>,
+    /// Names of the code versions uploaded by the owner.
+    code_versions: UnorderedSet<String>,
+    /// Code of the multisig per version name.
+    codes: LookupMap<String, Vec<u8>>,
+    /// Version used when creator doesn't specify one. Embedded code is used if not set.
+    default_code_version: Option<String>,
+}
+
+impl Default for MultisigFactory {
+    fn default() -> Self {
+        env::panic(b"The contract should be initialized before usage")
+    }
+}
+
+/// External interface for the callbacks to self.
+#[ext_contract(ext_self)]
+pub trait ExtSelf {
+    fn on_create(
+        &mut self,
+        account_id: AccountId,
+        members: Vec<MultisigMember>,
+        attached_deposit: U128,
+        predecessor_account_id: AccountId,
+    ) -> bool;
+}
 
 #[near_bindgen]
 impl MultisigFactory {
+    /// Initializes the factory with the owner that can manage code versions.
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        assert!(!env::state_exists(), "The contract is already initialized");
+        Self::internal_new(owner_id)
+    }
+
+    /// Migrates the factory deployed before it had the state. The old factory was stateless, so
+    /// its state is either missing or empty and gets overwritten (`#[init]` of this SDK version
+    /// doesn't check the state).
+    /// Can only be called by the factory account itself, e.g. in the same transaction as the
+    /// code deployment.
+    #[init]
+    pub fn migrate(owner_id: AccountId) -> Self {
+        assert_self();
+        assert!(
+            env::storage_read(b"STATE").map_or(true, |state| state.is_empty()),
+            "The contract is already migrated"
+        );
+        Self::internal_new(owner_id)
+    }
+
+    /// Creates a new multisig account `name.<factory>` with given members and number of
+    /// confirmations, passing the attached deposit to it.
+    /// - `code_version` - the version of the multisig code to deploy. Uses default version if not given.
+    /// If creation fails, the attached deposit is returned to the caller.
     #[payable]
     pub fn create(
         &mut self,
         name: AccountId,
         members: Vec<MultisigMember>,
         num_confirmations: u64,
+        code_version: Option<String>,
     ) -> Promise {
         let account_id = format!("{}.{}", name, env::current_account_id());
-        Promise::new(account_id)
+        assert!(
+            env::is_valid_account_id(account_id.as_bytes()),
+            "The multisig account ID is invalid"
+        );
+        let code = match code_version.or_else(|| self.default_code_version.clone()) {
+            Some(code_version) => self.codes.get(&code_version).expect("No such code version"),
+            None => CODE.to_vec(),
+        };
+        assert!(
+            self.multisig_account_ids.insert(&account_id),
+            "The multisig account ID already exists"
+        );
+        Promise::new(account_id.clone())
             .create_account()
-            .deploy_contract(CODE.to_vec())
+            .deploy_contract(code)
             .transfer(env::attached_deposit())
             .function_call(
                 b"new".to_vec(),
@@ -43,8 +131,387 @@ impl MultisigFactory {
                     .to_string()
                     .as_bytes()
                     .to_vec(),
-                0,
-                env::prepaid_gas() - CREATE_CALL_GAS,
+                NO_DEPOSIT,
+                env::prepaid_gas() - CREATE_CALL_GAS - ON_CREATE_CALL_GAS,
             )
+            .then(ext_self::on_create(
+                account_id,
+                members,
+                env::attached_deposit().into(),
+                env::predecessor_account_id(),
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                ON_CREATE_CALL_GAS,
+            ))
+    }
+
+    /// Callback after a multisig was created.
+    /// Records the multisig for each of its members if the creation succeeded.
+    /// Otherwise refunds the attached deposit and returns `false`.
+    pub fn on_create(
+        &mut self,
+        account_id: AccountId,
+        members: Vec<MultisigMember>,
+        attached_deposit: U128,
+        predecessor_account_id: AccountId,
+    ) -> PromiseOrValue<bool> {
+        assert_self();
+
+        if is_promise_success() {
+            for member in members {
+                let key = member.to_key();
+                let mut account_ids = self.multisigs_by_member.get(&key).unwrap_or_default();
+                account_ids.push(account_id.clone());
+                self.multisigs_by_member.insert(&key, &account_ids);
+            }
+            PromiseOrValue::Value(true)
+        } else {
+            self.multisig_account_ids.remove(&account_id);
+            env::log(
+                format!(
+                    "The multisig @{} creation has failed. Returning attached deposit of {} to @{}",
+                    account_id, attached_deposit.0, predecessor_account_id
+                )
+                .as_bytes(),
+            );
+            Promise::new(predecessor_account_id).transfer(attached_deposit.0);
+            PromiseOrValue::Value(false)
+        }
+    }
+
+    /// Adds new version of the multisig code. Can only be called by the owner.
+    pub fn add_code_version(&mut self, code_version: String, code: Base64VecU8) {
+        self.assert_owner();
+        assert!(
+            self.code_versions.insert(&code_version),
+            "The code version already exists"
+        );
+        self.codes.insert(&code_version, &code.into());
+    }
+
+    /// Removes given version of the multisig code. Can only be called by the owner.
+    pub fn remove_code_version(&mut self, code_version: String) {
+        self.assert_owner();
+        assert!(
+            self.code_versions.remove(&code_version),
+            "No such code version"
+        );
+        self.codes.remove(&code_version);
+        if self.default_code_version.as_ref() == Some(&code_version) {
+            self.default_code_version = None;
+        }
+    }
+
+    /// Sets the version used when creator doesn't specify one. Embedded code is used if `None`.
+    /// Can only be called by the owner.
+    pub fn set_default_code_version(&mut self, code_version: Option<String>) {
+        self.assert_owner();
+        if let Some(code_version) = code_version.as_ref() {
+            assert!(
+                self.code_versions.contains(code_version),
+                "No such code version"
+            );
+        }
+        self.default_code_version = code_version;
+    }
+
+    /********************************
+    View methods
+    ********************************/
+
+    pub fn get_owner_id(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    pub fn get_code_versions(&self) -> Vec<String> {
+        self.code_versions.to_vec()
+    }
+
+    pub fn get_default_code_version(&self) -> Option<String> {
+        self.default_code_version.clone()
+    }
+
+    /// Returns the total number of the multisigs created from this factory.
+    pub fn get_number_of_multisigs(&self) -> u64 {
+        self.multisig_account_ids.len()
+    }
+
+    /// Returns account IDs of the created multisigs, paginated by `from_index` and `limit`.
+    pub fn get_multisigs(&self, from_index: u64, limit: u64) -> Vec<AccountId> {
+        let elements = self.multisig_account_ids.as_vector();
+        (from_index..std::cmp::min(from_index.saturating_add(limit), elements.len()))
+            .filter_map(|index| elements.get(index))
+            .collect()
+    }
+
+    /// Returns account IDs of the multisigs created with given member, paginated by `from_index`
+    /// and `limit`.
+    pub fn get_multisigs_by_member(
+        &self,
+        member: MultisigMember,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<AccountId> {
+        self.multisigs_by_member
+            .get(&member.to_key())
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+}
+
+impl MultisigFactory {
+    fn internal_new(owner_id: AccountId) -> Self {
+        assert!(
+            env::is_valid_account_id(owner_id.as_bytes()),
+            "The owner account ID is invalid"
+        );
+        Self {
+            owner_id,
+            multisig_account_ids: UnorderedSet::new(b"a".to_vec()),
+            multisigs_by_member: LookupMap::new(b"m".to_vec()),
+            code_versions: UnorderedSet::new(b"v".to_vec()),
+            codes: LookupMap::new(b"c".to_vec()),
+            default_code_version: None,
+        }
+    }
+
+    fn assert_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Can only be called by the owner"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::{testing_env, MockedBlockchain, PromiseResult};
+
+    mod test_utils;
+    use test_utils::*;
+
+    fn context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(predecessor_account_id)
+            .account_balance(ntoy(100))
+    }
+
+    fn member(account_id: &str) -> MultisigMember {
+        MultisigMember::Account {
+            account_id: account_id.to_string(),
+        }
+    }
+
+    /// Creates the multisig `name.factory` with given members and resolves the callback.
+    fn create_multisig(
+        contract: &mut MultisigFactory,
+        name: &str,
+        members: Vec<MultisigMember>,
+        promise_result: PromiseResult,
+    ) -> bool {
+        testing_env!(context(account_creator())
+            .attached_deposit(ntoy(10))
+            .finish());
+        contract.create(name.to_string(), members.clone(), 1, None);
+
+        testing_env_with_promise_results(context(account_factory()).finish(), promise_result);
+        match contract.on_create(
+            format!("{}.{}", name, account_factory()),
+            members,
+            ntoy(10).into(),
+            account_creator(),
+        ) {
+            PromiseOrValue::Value(value) => value,
+            _ => panic!("Expected value"),
+        }
+    }
+
+    #[test]
+    fn test_create_success() {
+        testing_env!(context(account_owner()).finish());
+        let mut contract = MultisigFactory::new(account_owner());
+
+        assert!(create_multisig(
+            &mut contract,
+            "test",
+            vec![member("alice"), member("bob")],
+            PromiseResult::Successful(vec![]),
+        ));
+        assert_eq!(contract.get_number_of_multisigs(), 1);
+        assert_eq!(contract.get_multisigs(0, 10), vec!["test.factory"]);
+        assert_eq!(
+            contract.get_multisigs_by_member(member("bob"), 0, 10),
+            vec!["test.factory"]
+        );
+        assert!(contract
+            .get_multisigs_by_member(member("carol"), 0, 10)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_create_failure() {
+        testing_env!(context(account_owner()).finish());
+        let mut contract = MultisigFactory::new(account_owner());
+
+        assert!(!create_multisig(
+            &mut contract,
+            "test",
+            vec![member("alice")],
+            PromiseResult::Failed,
+        ));
+        assert_eq!(contract.get_number_of_multisigs(), 0);
+        assert!(contract
+            .get_multisigs_by_member(member("alice"), 0, 10)
+            .is_empty());
+
+        // The name can be used again after the failure.
+        assert!(create_multisig(
+            &mut contract,
+            "test",
+            vec![member("alice")],
+            PromiseResult::Successful(vec![]),
+        ));
+        assert_eq!(contract.get_number_of_multisigs(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The multisig account ID already exists")]
+    fn test_create_existing_name() {
+        testing_env!(context(account_owner()).finish());
+        let mut contract = MultisigFactory::new(account_owner());
+
+        create_multisig(
+            &mut contract,
+            "test",
+            vec![member("alice")],
+            PromiseResult::Successful(vec![]),
+        );
+        create_multisig(
+            &mut contract,
+            "test",
+            vec![member("alice")],
+            PromiseResult::Successful(vec![]),
+        );
+    }
+
+    #[test]
+    fn test_multisigs_pagination() {
+        testing_env!(context(account_owner()).finish());
+        let mut contract = MultisigFactory::new(account_owner());
+
+        for name in &["a", "b", "c"] {
+            create_multisig(
+                &mut contract,
+                name,
+                vec![member("alice")],
+                PromiseResult::Successful(vec![]),
+            );
+        }
+        assert_eq!(
+            contract.get_multisigs_by_member(member("alice"), 0, 2),
+            vec!["a.factory", "b.factory"]
+        );
+        assert_eq!(
+            contract.get_multisigs_by_member(member("alice"), 2, 2),
+            vec!["c.factory"]
+        );
+        assert!(contract
+            .get_multisigs_by_member(member("alice"), 3, 2)
+            .is_empty());
+        assert_eq!(
+            contract.get_multisigs_by_member(member("alice"), 1, u64::MAX),
+            vec!["b.factory", "c.factory"]
+        );
+        assert!(contract
+            .get_multisigs_by_member(member("alice"), u64::MAX, u64::MAX)
+            .is_empty());
+
+        assert_eq!(
+            contract.get_multisigs(1, u64::MAX),
+            vec!["b.factory", "c.factory"]
+        );
+        assert!(contract.get_multisigs(u64::MAX, u64::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_code_versions() {
+        testing_env!(context(account_owner()).finish());
+        let mut contract = MultisigFactory::new(account_owner());
+
+        contract.add_code_version("v1".to_string(), vec![1, 2, 3].into());
+        contract.add_code_version("v2".to_string(), vec![4, 5, 6].into());
+        contract.set_default_code_version(Some("v2".to_string()));
+        assert_eq!(contract.get_code_versions(), vec!["v1", "v2"]);
+        assert_eq!(contract.get_default_code_version(), Some("v2".to_string()));
+
+        // Creating with the uploaded code version.
+        testing_env!(context(account_creator())
+            .attached_deposit(ntoy(10))
+            .finish());
+        contract.create(
+            "test".to_string(),
+            vec![member("alice")],
+            1,
+            Some("v1".to_string()),
+        );
+
+        // Removing the default version resets it to the embedded code.
+        testing_env!(context(account_owner()).finish());
+        contract.remove_code_version("v2".to_string());
+        assert_eq!(contract.get_code_versions(), vec!["v1"]);
+        assert_eq!(contract.get_default_code_version(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "No such code version")]
+    fn test_create_unknown_code_version() {
+        testing_env!(context(account_owner()).finish());
+        let mut contract = MultisigFactory::new(account_owner());
+
+        testing_env!(context(account_creator())
+            .attached_deposit(ntoy(10))
+            .finish());
+        contract.create(
+            "test".to_string(),
+            vec![member("alice")],
+            1,
+            Some("v1".to_string()),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only be called by the owner")]
+    fn test_add_code_version_not_owner() {
+        testing_env!(context(account_owner()).finish());
+        let mut contract = MultisigFactory::new(account_owner());
+
+        testing_env!(context(account_creator()).finish());
+        contract.add_code_version("v1".to_string(), vec![1, 2, 3].into());
+    }
+
+    #[test]
+    fn test_migrate() {
+        testing_env!(context(account_factory()).finish());
+        // The state of the stateless factory.
+        env::storage_write(b"STATE", &[]);
+
+        let contract = MultisigFactory::migrate(account_owner());
+        assert_eq!(contract.get_owner_id(), account_owner());
+        assert_eq!(contract.get_number_of_multisigs(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract is already migrated")]
+    fn test_migrate_twice() {
+        testing_env!(context(account_factory()).finish());
+        env::storage_write(b"STATE", &[1]);
+
+        MultisigFactory::migrate(account_owner());
     }
 }
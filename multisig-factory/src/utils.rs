@@ -0,0 +1,17 @@
+use near_sdk::{env, PromiseResult};
+
+pub fn assert_self() {
+    assert_eq!(env::predecessor_account_id(), env::current_account_id());
+}
+
+pub fn is_promise_success() -> bool {
+    assert_eq!(
+        env::promise_results_count(),
+        1,
+        "Contract expected a result on the callback"
+    );
+    match env::promise_result(0) {
+        PromiseResult::Successful(_) => true,
+        _ => false,
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 use std::convert::TryFrom;
 
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::UnorderedMap;
+use near_sdk::collections::{LookupSet, UnorderedMap};
 use near_sdk::json_types::{Base58PublicKey, Base64VecU8, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, near_bindgen, AccountId, Promise, PromiseOrValue, PublicKey};
@@ -15,6 +15,9 @@ const REQUEST_COOLDOWN: u64 = 900_000_000_000;
 
 pub type RequestId = u32;
 
+/// Prefix of the public keys that can sign requests off-chain.
+const SIGNER_KEYS_PREFIX: &[u8] = b"s";
+
 /// Permissions for function call access key.
 #[derive(Clone, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -76,6 +79,16 @@ pub struct MultiSigRequestWithSigner {
     added_timestamp: u64,
 }
 
+/// Signature of the request produced off-chain by one of the multisig keys.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeySignature {
+    /// Public key registered with `register_signer_key`.
+    public_key: Base58PublicKey,
+    /// ed25519 signature over the request payload (see `get_request_payload`).
+    signature: Base64VecU8,
+}
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct MultiSigContract {
@@ -88,6 +101,28 @@ pub struct MultiSigContract {
     active_requests_limit: u32,
 }
 
+// The set is kept outside of the contract struct, so deployed contracts don't need a state migration
+fn signer_keys() -> LookupSet<PublicKey> {
+    LookupSet::new(SIGNER_KEYS_PREFIX.to_vec())
+}
+
+// Verifies ed25519 signature of the given message, keys of other curves are not supported
+fn verify_signature(public_key: &PublicKey, signature: &[u8], message: &[u8]) -> bool {
+    use ed25519_dalek::Verifier;
+    if public_key.len() != 33 || public_key[0] != 0 {
+        return false;
+    }
+    let public_key = match ed25519_dalek::PublicKey::from_bytes(&public_key[1..]) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    let signature = match ed25519_dalek::Signature::try_from(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    public_key.verify(message, &signature).is_ok()
+}
+
 // If you haven't initialized the contract with new(num_confirmations: u32)
 impl Default for MultiSigContract {
     fn default() -> Self {
@@ -212,6 +247,8 @@ impl MultiSigContract {
                     }
                     // remove num_requests_pk entry for public_key
                     self.num_requests_pk.remove(&pk);
+                    // deleted key can't sign requests off-chain anymore
+                    signer_keys().remove(&pk);
                     promise.delete_key(pk)
                 }
                 MultiSigRequestAction::FunctionCall {
@@ -265,9 +302,71 @@ impl MultiSigContract {
         }
     }
 
+    /// Allow the signing key to approve requests off-chain via `execute_with_signatures`.
+    /// The contract can't list access keys of its account, so each key registers itself by
+    /// calling this method. The key stays registered until it's deleted with `DeleteKey`.
+    /// Keys added without this method in their allowed method names must be re-added first.
+    pub fn register_signer_key(&mut self) {
+        assert_eq!(
+            env::current_account_id(),
+            env::predecessor_account_id(),
+            "Predecessor account must much current account"
+        );
+        let public_key = env::signer_account_pk();
+        assert!(
+            public_key.len() == 33 && public_key[0] == 0,
+            "Only ed25519 keys can sign requests"
+        );
+        signer_keys().insert(&public_key);
+    }
+
+    /// Execute request approved off-chain by keys of the multisig.
+    /// Each signature must be produced by a distinct registered key over the payload returned by
+    /// `get_request_payload` and their number must reach `num_confirmations`.
+    /// Consumes current `request_nonce`, so the same signatures can't be used twice.
+    pub fn execute_with_signatures(
+        &mut self,
+        request: MultiSigRequest,
+        signatures: Vec<KeySignature>,
+    ) -> PromiseOrValue<bool> {
+        let payload = self.request_payload_hash(&request);
+        let signer_keys = signer_keys();
+        let mut signers = HashSet::new();
+        for key_signature in signatures {
+            let public_key: PublicKey = key_signature.public_key.into();
+            assert!(
+                signer_keys.contains(&public_key),
+                "Signature is not from a registered key of this multisig"
+            );
+            assert!(
+                verify_signature(&public_key, &key_signature.signature.0, &payload),
+                "Invalid signature"
+            );
+            assert!(
+                signers.insert(public_key),
+                "Duplicate signature from the same key"
+            );
+        }
+        assert!(
+            signers.len() as u32 >= self.num_confirmations,
+            "Not enough signatures to execute the request"
+        );
+        self.request_nonce += 1;
+        self.execute_request(request)
+    }
+
     /********************************
     Helper methods
     ********************************/
+    // Returns sha256 hash of borsh serialized multisig account id, current request_nonce and the request
+    fn request_payload_hash(&self, request: &MultiSigRequest) -> Vec<u8> {
+        let mut data = env::current_account_id()
+            .try_to_vec()
+            .expect("Failed to serialize");
+        data.extend_from_slice(&self.request_nonce.to_le_bytes());
+        data.extend(request.try_to_vec().expect("Failed to serialize"));
+        env::sha256(&data)
+    }
     // removes request, removes confirmations and reduces num_requests_pk - used in delete, delete_key, and confirm
     fn remove_request(&mut self, request_id: RequestId) -> MultiSigRequest {
         // remove confirmations for this request
@@ -352,6 +451,16 @@ impl MultiSigContract {
     pub fn get_request_nonce(&self) -> u32 {
         self.request_nonce
     }
+
+    pub fn is_signer_key(&self, public_key: Base58PublicKey) -> bool {
+        signer_keys().contains(&public_key.into())
+    }
+
+    /// Returns hash that keys should sign to approve given request via `execute_with_signatures`.
+    /// Only valid until `request_nonce` changes.
+    pub fn get_request_payload(&self, request: MultiSigRequest) -> Base64VecU8 {
+        self.request_payload_hash(&request).into()
+    }
 }
 
 #[cfg(test)]
@@ -756,4 +865,176 @@ mod tests {
             });
         }
     }
+
+    fn signer_keypair(seed: u8) -> ed25519_dalek::Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[seed; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        ed25519_dalek::Keypair { secret, public }
+    }
+
+    fn keypair_public_key(keypair: &ed25519_dalek::Keypair) -> PublicKey {
+        let mut public_key = vec![0];
+        public_key.extend_from_slice(keypair.public.as_bytes());
+        public_key
+    }
+
+    fn sign_request(
+        c: &MultiSigContract,
+        request: &MultiSigRequest,
+        keypair: &ed25519_dalek::Keypair,
+    ) -> KeySignature {
+        use ed25519_dalek::Signer;
+        let payload = c.get_request_payload(request.clone());
+        KeySignature {
+            public_key: Base58PublicKey(keypair_public_key(keypair)),
+            signature: keypair.sign(&payload.0).to_bytes().to_vec().into(),
+        }
+    }
+
+    fn register_signer_keys(
+        c: &mut MultiSigContract,
+        keypairs: &[ed25519_dalek::Keypair],
+        amount: Balance,
+    ) {
+        for keypair in keypairs {
+            testing_env!(context_with_key(keypair_public_key(keypair), amount));
+            c.register_signer_key();
+        }
+    }
+
+    #[test]
+    fn test_execute_with_signatures() {
+        let amount = 1_000;
+        let keypairs = vec![signer_keypair(1), signer_keypair(2), signer_keypair(3)];
+        testing_env!(context_with_key(keypair_public_key(&keypairs[0]), amount));
+        let mut c = MultiSigContract::new(2);
+        register_signer_keys(&mut c, &keypairs, amount);
+        let request = MultiSigRequest {
+            receiver_id: alice(),
+            actions: vec![MultiSigRequestAction::SetNumConfirmations {
+                num_confirmations: 3,
+            }],
+        };
+        let signatures = vec![
+            sign_request(&c, &request, &keypairs[0]),
+            sign_request(&c, &request, &keypairs[2]),
+        ];
+        c.execute_with_signatures(request, signatures);
+        assert_eq!(c.get_num_confirmations(), 3);
+        assert_eq!(c.get_request_nonce(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid signature")]
+    fn test_execute_with_signatures_replay() {
+        let amount = 1_000;
+        let keypairs = vec![signer_keypair(1), signer_keypair(2)];
+        testing_env!(context_with_key(keypair_public_key(&keypairs[0]), amount));
+        let mut c = MultiSigContract::new(2);
+        register_signer_keys(&mut c, &keypairs, amount);
+        let request = MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer { amount: 100.into() }],
+        };
+        let signatures = || {
+            vec![
+                sign_request(&c, &request, &keypairs[0]),
+                sign_request(&c, &request, &keypairs[1]),
+            ]
+        };
+        let first = signatures();
+        let second = signatures();
+        c.execute_with_signatures(request.clone(), first);
+        c.execute_with_signatures(request, second);
+    }
+
+    #[test]
+    #[should_panic(expected = "Signature is not from a registered key of this multisig")]
+    fn test_execute_with_signatures_unregistered_key() {
+        let amount = 1_000;
+        let keypairs = vec![signer_keypair(1), signer_keypair(2)];
+        testing_env!(context_with_key(keypair_public_key(&keypairs[0]), amount));
+        let mut c = MultiSigContract::new(2);
+        register_signer_keys(&mut c, &keypairs[..1], amount);
+        let request = MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer {
+                amount: amount.into(),
+            }],
+        };
+        let signatures = vec![
+            sign_request(&c, &request, &keypairs[0]),
+            sign_request(&c, &request, &keypairs[1]),
+        ];
+        c.execute_with_signatures(request, signatures);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough signatures to execute the request")]
+    fn test_execute_with_signatures_not_enough() {
+        let amount = 1_000;
+        let keypairs = vec![signer_keypair(1), signer_keypair(2)];
+        testing_env!(context_with_key(keypair_public_key(&keypairs[0]), amount));
+        let mut c = MultiSigContract::new(2);
+        register_signer_keys(&mut c, &keypairs, amount);
+        let request = MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer {
+                amount: amount.into(),
+            }],
+        };
+        let signatures = vec![sign_request(&c, &request, &keypairs[0])];
+        c.execute_with_signatures(request, signatures);
+    }
+
+    #[test]
+    fn test_delete_key_unregisters_signer_key() {
+        let amount = 1_000;
+        let keypair = signer_keypair(1);
+        let public_key = keypair_public_key(&keypair);
+        testing_env!(context_with_key(public_key.clone(), amount));
+        let mut c = MultiSigContract::new(1);
+        c.register_signer_key();
+        assert!(c.is_signer_key(Base58PublicKey(public_key.clone())));
+        c.add_request_and_confirm(MultiSigRequest {
+            receiver_id: alice(),
+            actions: vec![MultiSigRequestAction::DeleteKey {
+                public_key: Base58PublicKey(public_key.clone()),
+            }],
+        });
+        assert!(!c.is_signer_key(Base58PublicKey(public_key)));
+    }
+
+    #[test]
+    fn test_readd_key_registers_signer_key() {
+        let amount = 1_000;
+        let keypair = signer_keypair(1);
+        let public_key = keypair_public_key(&keypair);
+        testing_env!(context_with_key(public_key.clone(), amount));
+        let mut c = MultiSigContract::new(1);
+        c.add_request_and_confirm(MultiSigRequest {
+            receiver_id: alice(),
+            actions: vec![
+                MultiSigRequestAction::DeleteKey {
+                    public_key: Base58PublicKey(public_key.clone()),
+                },
+                MultiSigRequestAction::AddKey {
+                    public_key: Base58PublicKey(public_key.clone()),
+                    permission: Some(FunctionCallPermission {
+                        allowance: None,
+                        receiver_id: alice(),
+                        method_names: vec![
+                            "add_request".to_string(),
+                            "delete_request".to_string(),
+                            "confirm".to_string(),
+                            "register_signer_key".to_string(),
+                            "execute_with_signatures".to_string(),
+                        ],
+                    }),
+                },
+            ],
+        });
+        c.register_signer_key();
+        assert!(c.is_signer_key(Base58PublicKey(public_key)));
+    }
 }
@@ -1,15 +1,17 @@
 use std::collections::HashSet;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet};
 use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
-    env, near_bindgen, serde_json, AccountId, BorshStorageKey, Gas, PanicOnDefault, Promise,
-    PromiseOrValue, PublicKey,
+    env, near_bindgen, serde_json, AccountId, BorshStorageKey, CurveType, Gas, PanicOnDefault,
+    Promise, PromiseOrValue, PublicKey,
 };
 
+mod migration;
+
 /// Unlimited allowance for multisig keys.
 const DEFAULT_ALLOWANCE: u128 = 0;
 
@@ -20,7 +22,14 @@ const REQUEST_COOLDOWN: u64 = 900_000_000_000;
 const ACTIVE_REQUESTS_LIMIT: u32 = 12;
 
 /// Default set of methods that access key should have.
-const MULTISIG_METHOD_NAMES: &str = "add_request,delete_request,confirm,add_and_confirm_request";
+const MULTISIG_METHOD_NAMES: &str = "add_request,delete_request,confirm,add_and_confirm_request,\
+    revoke_confirmation,execute_with_signatures";
+
+/// Standard name used for events emitted by this contract.
+const EVENT_STANDARD: &str = "multisig";
+
+/// Version of the events emitted by this contract.
+const EVENT_VERSION: &str = "1.0.0";
 
 pub type RequestId = u32;
 
@@ -70,6 +79,13 @@ pub enum MultiSigRequestAction {
     /// The REQUEST_COOLDOWN for requests is 15min
     /// Worst gas attack a malicious keyholder could do is 12 requests every 15min
     SetActiveRequestsLimit { active_requests_limit: u32 },
+    /// Deletes key, either from this account or from another account.
+    /// Keys of the members of this multisig should be removed via `DeleteMember` instead.
+    DeleteKey { public_key: PublicKey },
+    /// Deletes receiver's account, sending all remaining funds to the beneficiary.
+    DeleteAccount { beneficiary_id: AccountId },
+    /// Stakes given amount with the given validator key. Only works on this account.
+    Stake { amount: U128, public_key: PublicKey },
 }
 
 /// The request the user makes specifying the receiving account and actions they want to execute (1 tx)
@@ -91,6 +107,16 @@ pub struct MultiSigRequestWithSigner {
     added_timestamp: u64,
 }
 
+/// Signature of the request produced off-chain by a member's access key.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MemberSignature {
+    /// Public key of the member, must be an `AccessKey` member of the multisig.
+    public_key: PublicKey,
+    /// ed25519 signature over the request payload (see `get_request_payload`).
+    signature: Base64VecU8,
+}
+
 /// Represents member of the multsig: either account or access key to given account.
 #[derive(Debug, BorshDeserialize, BorshSerialize, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde", untagged)]
@@ -139,6 +165,36 @@ fn assert(condition: bool, error: &str) {
     }
 }
 
+/// Logs an event in the NEP-297 format, so indexers can pick it up.
+fn log_event(event: &str, data: serde_json::Value) {
+    env::log_str(&format!(
+        "EVENT_JSON:{}",
+        serde_json::json!({
+            "standard": EVENT_STANDARD,
+            "version": EVENT_VERSION,
+            "event": event,
+            "data": [data],
+        })
+    ));
+}
+
+/// Verifies ed25519 signature of the given message. Returns false for keys of other curves.
+fn verify_signature(public_key: &PublicKey, signature: &[u8], message: &[u8]) -> bool {
+    use ed25519_dalek::Verifier;
+    if public_key.curve_type() != CurveType::ED25519 {
+        return false;
+    }
+    let public_key = match ed25519_dalek::PublicKey::from_bytes(&public_key.as_bytes()[1..]) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    let signature = match ed25519_dalek::Signature::try_from(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    public_key.verify(message, &signature).is_ok()
+}
+
 #[near_bindgen]
 impl MultiSigContract {
     /// Initialize multisig contract.
@@ -260,6 +316,28 @@ impl MultiSigContract {
                         promise.add_full_access_key(public_key.into())
                     }
                 }
+                MultiSigRequestAction::DeleteKey { public_key } => {
+                    if receiver_id == env::current_account_id() {
+                        assert(
+                            !self.members.contains(&MultisigMember::AccessKey {
+                                public_key: public_key.clone(),
+                            }),
+                            "Can not delete key of a member, use DeleteMember instead",
+                        );
+                    }
+                    promise.delete_key(public_key)
+                }
+                MultiSigRequestAction::DeleteAccount { beneficiary_id } => {
+                    assert(
+                        beneficiary_id != receiver_id,
+                        "Beneficiary must be different from the account being deleted",
+                    );
+                    promise.delete_account(beneficiary_id)
+                }
+                MultiSigRequestAction::Stake { amount, public_key } => {
+                    self.assert_self_request(receiver_id.clone());
+                    promise.stake(amount.into(), public_key)
+                }
                 MultiSigRequestAction::FunctionCall {
                     method_name,
                     args,
@@ -314,6 +392,68 @@ impl MultiSigContract {
         }
     }
 
+    /// Execute request approved off-chain by members of the multisig.
+    /// Each signature must be produced by a distinct `AccessKey` member over the payload returned
+    /// by `get_request_payload` and their number must reach `num_confirmations`.
+    /// Consumes current `request_nonce`, so the same signatures can't be used twice.
+    pub fn execute_with_signatures(
+        &mut self,
+        request: MultiSigRequest,
+        signatures: Vec<MemberSignature>,
+    ) -> PromiseOrValue<bool> {
+        let payload = self.request_payload_hash(&request);
+        let mut signers = HashSet::new();
+        for member_signature in signatures {
+            let member = MultisigMember::AccessKey {
+                public_key: member_signature.public_key.clone(),
+            };
+            assert(
+                self.members.contains(&member),
+                "Signature is not from a member of this multisig",
+            );
+            assert(
+                signers.insert(member.to_string()),
+                "Duplicate signature from the same member",
+            );
+            assert(
+                verify_signature(
+                    &member_signature.public_key,
+                    &member_signature.signature.0,
+                    &payload,
+                ),
+                "Invalid signature",
+            );
+        }
+        assert(
+            signers.len() as u32 >= self.num_confirmations,
+            "Not enough signatures to execute the request",
+        );
+        self.request_nonce += 1;
+        self.execute_request(request)
+    }
+
+    /// Revoke confirmation of the given request previously made by the current member.
+    /// Allows to withdraw the vote before the request gathers enough confirmations to execute.
+    pub fn revoke_confirmation(&mut self, request_id: RequestId) {
+        self.assert_valid_request(request_id);
+        let member = self
+            .current_member()
+            .unwrap_or_else(|| env::panic_str("Must be validated above"));
+        let mut confirmations = self.confirmations.get(&request_id).unwrap();
+        assert(
+            confirmations.remove(&member.to_string()),
+            "This request has not been confirmed with this key",
+        );
+        self.confirmations.insert(&request_id, &confirmations);
+        log_event(
+            "revoke_confirmation",
+            serde_json::json!({
+                "request_id": request_id,
+                "member": member,
+            }),
+        );
+    }
+
     /********************************
     Helper methods
     ********************************/
@@ -378,6 +518,23 @@ impl MultiSigContract {
         }
     }
 
+    /// Returns sha256 hash of the data members sign off-chain to approve the request.
+    /// Data is borsh serialized multisig account id, current request nonce and the request itself:
+    /// account id prevents replaying signatures on another multisig with the same members,
+    /// nonce prevents replaying them on this one.
+    fn request_payload_hash(&self, request: &MultiSigRequest) -> Vec<u8> {
+        let mut data = env::current_account_id()
+            .try_to_vec()
+            .unwrap_or_else(|_| env::panic_str("Failed to serialize"));
+        data.extend_from_slice(&self.request_nonce.to_le_bytes());
+        data.extend(
+            request
+                .try_to_vec()
+                .unwrap_or_else(|_| env::panic_str("Failed to serialize")),
+        );
+        env::sha256(&data)
+    }
+
     /// Removes request, removes confirmations and reduces num_requests_pk - used in delete, delete_key, and confirm
     fn remove_request(&mut self, request_id: RequestId) -> MultiSigRequest {
         // remove confirmations for this request
@@ -476,6 +633,12 @@ impl MultiSigContract {
     pub fn get_request_nonce(&self) -> u32 {
         self.request_nonce
     }
+
+    /// Returns hash that members should sign to approve given request via `execute_with_signatures`.
+    /// Only valid until `request_nonce` changes.
+    pub fn get_request_payload(&self, request: MultiSigRequest) -> Base64VecU8 {
+        self.request_payload_hash(&request).into()
+    }
 }
 
 #[cfg(test)]
@@ -718,6 +881,45 @@ mod tests {
         assert_eq!(c.get_num_requests_per_member(new_member), 0);
     }
 
+    #[test]
+    fn test_readd_member_key() {
+        let amount = 1_000;
+        testing_env!(context_with_key(
+            PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            amount
+        ));
+        let mut c = MultiSigContract::new(members(), 1);
+        let member = members()[3].clone();
+        // Re-adding the member replaces its access key with the current method names.
+        let request = MultiSigRequest {
+            receiver_id: alice(),
+            actions: vec![
+                MultiSigRequestAction::DeleteMember {
+                    member: member.clone(),
+                },
+                MultiSigRequestAction::AddMember {
+                    member: member.clone(),
+                },
+            ],
+        };
+        c.add_request_and_confirm(request);
+        assert_eq!(c.requests.len(), 0);
+        assert!(c.get_members().contains(&member));
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        let receipt = receipts.last().unwrap();
+        assert_eq!(receipt.actions.len(), 2);
+        assert!(matches!(
+            receipt.actions[0],
+            near_sdk::mock::VmAction::DeleteKey { .. }
+        ));
+        match &receipt.actions[1] {
+            near_sdk::mock::VmAction::AddKeyWithFunctionCall { method_names, .. } => {
+                assert_eq!(method_names.join(","), MULTISIG_METHOD_NAMES)
+            }
+            _ => panic!("Expected the access key to be added"),
+        }
+    }
+
     #[test]
     #[should_panic]
     fn test_panics_add_key_different_account() {
@@ -743,6 +945,108 @@ mod tests {
         c.add_request_and_confirm(request);
     }
 
+    #[test]
+    #[should_panic(expected = "Can not delete key of a member, use DeleteMember instead")]
+    fn test_panics_delete_member_key() {
+        let amount = 1_000;
+        testing_env!(context_with_key(
+            PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            amount
+        ));
+        let mut c = MultiSigContract::new(members(), 1);
+        c.add_request_and_confirm(MultiSigRequest {
+            receiver_id: alice(),
+            actions: vec![MultiSigRequestAction::DeleteKey {
+                public_key: PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            }],
+        });
+    }
+
+    #[test]
+    fn test_delete_non_member_key() {
+        let amount = 1_000;
+        testing_env!(context_with_key(
+            PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            amount
+        ));
+        let mut c = MultiSigContract::new(members(), 1);
+        c.add_request_and_confirm(MultiSigRequest {
+            receiver_id: alice(),
+            actions: vec![MultiSigRequestAction::DeleteKey {
+                public_key: "ed25519:4wBqpZM9xaSheZzJSMawUKKwhdpChKbZ5eu5ky4Vigw"
+                    .parse()
+                    .unwrap(),
+            }],
+        });
+        assert_eq!(c.requests.len(), 0);
+        assert_eq!(c.get_members().len(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Beneficiary must be different from the account being deleted")]
+    fn test_panics_delete_account_to_itself() {
+        let amount = 1_000;
+        testing_env!(context_with_key(
+            PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            amount
+        ));
+        let mut c = MultiSigContract::new(members(), 1);
+        c.add_request_and_confirm(MultiSigRequest {
+            receiver_id: alice(),
+            actions: vec![MultiSigRequestAction::DeleteAccount {
+                beneficiary_id: alice(),
+            }],
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "This method only works when receiver_id is equal to current_account_id"
+    )]
+    fn test_panics_stake_different_account() {
+        let amount = 1_000;
+        testing_env!(context_with_key(
+            PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            amount
+        ));
+        let mut c = MultiSigContract::new(members(), 1);
+        c.add_request_and_confirm(MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Stake {
+                amount: amount.into(),
+                public_key: PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            }],
+        });
+    }
+
+    #[test]
+    fn test_action_borsh_discriminants() {
+        // Requests are stored with Borsh, so existing variants must keep their discriminants.
+        let discriminant = |action: MultiSigRequestAction| action.try_to_vec().unwrap()[0];
+        assert_eq!(
+            discriminant(MultiSigRequestAction::FunctionCall {
+                method_name: "test".to_string(),
+                args: Base64VecU8(vec![]),
+                deposit: 0.into(),
+                gas: 0.into(),
+            }),
+            6
+        );
+        assert_eq!(
+            discriminant(MultiSigRequestAction::SetActiveRequestsLimit {
+                active_requests_limit: 1
+            }),
+            8
+        );
+        assert_eq!(
+            discriminant(MultiSigRequestAction::Stake {
+                amount: 0.into(),
+                public_key: PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            }),
+            11
+        );
+    }
+
     #[test]
     fn test_change_num_confirmations() {
         let amount = 1_000;
@@ -783,6 +1087,177 @@ mod tests {
         c.confirm(request_id);
     }
 
+    #[test]
+    fn test_revoke_confirmation() {
+        let amount = 1_000;
+        testing_env!(context_with_key(
+            PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            amount
+        ));
+        let mut c = MultiSigContract::new(members(), 3);
+        let request_id = c.add_request_and_confirm(MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer {
+                amount: amount.into(),
+            }],
+        });
+        assert_eq!(c.get_confirmations(request_id).len(), 1);
+        c.revoke_confirmation(request_id);
+        assert_eq!(c.get_confirmations(request_id).len(), 0);
+        assert_eq!(c.requests.len(), 1);
+        // Can confirm again after revoking.
+        c.confirm(request_id);
+        assert_eq!(c.get_confirmations(request_id).len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "This request has not been confirmed with this key")]
+    fn test_panics_revoke_without_confirmation() {
+        let amount = 1_000;
+        testing_env!(context_with_key(
+            PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            amount
+        ));
+        let mut c = MultiSigContract::new(members(), 3);
+        let request_id = c.add_request(MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer {
+                amount: amount.into(),
+            }],
+        });
+        c.revoke_confirmation(request_id);
+    }
+
+    /// Returns deterministic ed25519 key pair for signing requests off-chain.
+    fn signer_keypair(seed: u8) -> ed25519_dalek::Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[seed; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        ed25519_dalek::Keypair { secret, public }
+    }
+
+    fn keypair_public_key(keypair: &ed25519_dalek::Keypair) -> PublicKey {
+        let mut data = vec![0];
+        data.extend_from_slice(keypair.public.as_bytes());
+        PublicKey::try_from(data).unwrap()
+    }
+
+    fn sign_request(
+        c: &MultiSigContract,
+        request: &MultiSigRequest,
+        keypair: &ed25519_dalek::Keypair,
+    ) -> MemberSignature {
+        use ed25519_dalek::Signer;
+        let payload = c.get_request_payload(request.clone());
+        MemberSignature {
+            public_key: keypair_public_key(keypair),
+            signature: keypair.sign(&payload.0).to_bytes().to_vec().into(),
+        }
+    }
+
+    fn signature_members(keypairs: &[ed25519_dalek::Keypair]) -> Vec<MultisigMember> {
+        keypairs
+            .iter()
+            .map(|keypair| MultisigMember::AccessKey {
+                public_key: keypair_public_key(keypair),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_execute_with_signatures() {
+        testing_env!(context_with_account(bob(), 1_000));
+        let keypairs = vec![signer_keypair(1), signer_keypair(2), signer_keypair(3)];
+        let mut c = MultiSigContract::new(signature_members(&keypairs), 2);
+        let request = MultiSigRequest {
+            receiver_id: alice(),
+            actions: vec![MultiSigRequestAction::SetNumConfirmations {
+                num_confirmations: 3,
+            }],
+        };
+        let signatures = vec![
+            sign_request(&c, &request, &keypairs[0]),
+            sign_request(&c, &request, &keypairs[2]),
+        ];
+        c.execute_with_signatures(request, signatures);
+        assert_eq!(c.get_num_confirmations(), 3);
+        assert_eq!(c.get_request_nonce(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid signature")]
+    fn test_execute_with_signatures_replay() {
+        testing_env!(context_with_account(bob(), 1_000));
+        let keypairs = vec![signer_keypair(1), signer_keypair(2)];
+        let mut c = MultiSigContract::new(signature_members(&keypairs), 2);
+        let request = MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer {
+                amount: 1_000.into(),
+            }],
+        };
+        let signatures = || {
+            vec![
+                sign_request(&c, &request, &keypairs[0]),
+                sign_request(&c, &request, &keypairs[1]),
+            ]
+        };
+        let first = signatures();
+        let second = signatures();
+        c.execute_with_signatures(request.clone(), first);
+        c.execute_with_signatures(request, second);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate signature from the same member")]
+    fn test_execute_with_signatures_duplicate() {
+        testing_env!(context_with_account(bob(), 1_000));
+        let keypairs = vec![signer_keypair(1), signer_keypair(2)];
+        let mut c = MultiSigContract::new(signature_members(&keypairs), 2);
+        let request = MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer {
+                amount: 1_000.into(),
+            }],
+        };
+        let signatures = vec![
+            sign_request(&c, &request, &keypairs[0]),
+            sign_request(&c, &request, &keypairs[0]),
+        ];
+        c.execute_with_signatures(request, signatures);
+    }
+
+    #[test]
+    #[should_panic(expected = "Signature is not from a member of this multisig")]
+    fn test_execute_with_signatures_non_member() {
+        testing_env!(context_with_account(bob(), 1_000));
+        let keypairs = vec![signer_keypair(1), signer_keypair(2)];
+        let mut c = MultiSigContract::new(signature_members(&keypairs), 1);
+        let request = MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer {
+                amount: 1_000.into(),
+            }],
+        };
+        let signatures = vec![sign_request(&c, &request, &signer_keypair(3))];
+        c.execute_with_signatures(request, signatures);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough signatures to execute the request")]
+    fn test_execute_with_signatures_not_enough() {
+        testing_env!(context_with_account(bob(), 1_000));
+        let keypairs = vec![signer_keypair(1), signer_keypair(2)];
+        let mut c = MultiSigContract::new(signature_members(&keypairs), 2);
+        let request = MultiSigRequest {
+            receiver_id: bob(),
+            actions: vec![MultiSigRequestAction::Transfer {
+                amount: 1_000.into(),
+            }],
+        };
+        let signatures = vec![sign_request(&c, &request, &keypairs[0])];
+        c.execute_with_signatures(request, signatures);
+    }
+
     #[test]
     #[should_panic]
     fn test_panics_delete_request() {
@@ -866,6 +1341,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_migrate_from_v1() {
+        use crate::migration::*;
+
+        testing_env!(context_with_key(
+            PublicKey::try_from(TEST_KEY.to_vec()).unwrap(),
+            1_000
+        ));
+        let first_key = PublicKey::try_from(TEST_KEY.to_vec()).unwrap();
+        let second_key: PublicKey = "ed25519:Eg2jtsiMrprn7zgKKUk79qM1hWhANsFyE6JSX4txLEuy"
+            .parse()
+            .unwrap();
+        let mut old = LegacyMultiSigContract::new(2);
+        old.requests.insert(
+            &0,
+            &LegacyMultiSigRequestWithSigner {
+                request: LegacyMultiSigRequest {
+                    receiver_id: alice(),
+                    actions: vec![LegacyMultiSigRequestAction::DeleteKey {
+                        public_key: second_key.clone(),
+                    }],
+                },
+                signer_pk: first_key.clone(),
+                added_timestamp: 10,
+            },
+        );
+        old.confirmations
+            .insert(&0, &vec![first_key.clone()].into_iter().collect());
+        old.num_requests_pk.insert(&first_key, &1);
+        old.request_nonce = 1;
+        env::state_write(&old);
+
+        let c = MultiSigContract::migrate(vec![MultisigMember::AccessKey {
+            public_key: second_key.clone(),
+        }]);
+        assert_eq!(c.get_members().len(), 2);
+        assert_eq!(c.get_num_confirmations(), 2);
+        assert_eq!(c.get_request_nonce(), 1);
+        assert_eq!(c.list_request_ids(), vec![0]);
+        assert_eq!(
+            c.get_request(0),
+            MultiSigRequest {
+                receiver_id: alice(),
+                actions: vec![MultiSigRequestAction::DeleteMember {
+                    member: MultisigMember::AccessKey {
+                        public_key: second_key
+                    },
+                }],
+            }
+        );
+        let first_member = MultisigMember::AccessKey {
+            public_key: first_key,
+        };
+        assert_eq!(c.get_confirmations(0), vec![first_member.to_string()]);
+        assert_eq!(c.get_num_requests_per_member(first_member), 1);
+    }
+
     #[test]
     #[should_panic]
     fn test_too_many_confirmations() {
@@ -0,0 +1,194 @@
+//! Migration of the state of multisig v1 (`multisig` contract) into multisig2.
+//!
+//! v1 stores its collections under `b"r"`, `b"c"` and `b"k"` prefixes, which don't overlap with
+//! `StorageKeys` of multisig2, so only the `STATE` key is shared between the two layouts.
+//! Borsh representation of `AccountId` and `PublicKey` is the same in both versions.
+use crate::*;
+
+/// `MultiSigRequestAction` as stored by multisig v1.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) enum LegacyMultiSigRequestAction {
+    Transfer {
+        amount: U128,
+    },
+    CreateAccount,
+    DeployContract {
+        code: Base64VecU8,
+    },
+    AddKey {
+        public_key: PublicKey,
+        permission: Option<FunctionCallPermission>,
+    },
+    DeleteKey {
+        public_key: PublicKey,
+    },
+    FunctionCall {
+        method_name: String,
+        args: Base64VecU8,
+        deposit: U128,
+        gas: U64,
+    },
+    SetNumConfirmations {
+        num_confirmations: u32,
+    },
+    SetActiveRequestsLimit {
+        active_requests_limit: u32,
+    },
+}
+
+impl From<LegacyMultiSigRequestAction> for MultiSigRequestAction {
+    fn from(action: LegacyMultiSigRequestAction) -> Self {
+        match action {
+            LegacyMultiSigRequestAction::Transfer { amount } => Self::Transfer { amount },
+            LegacyMultiSigRequestAction::CreateAccount => Self::CreateAccount,
+            LegacyMultiSigRequestAction::DeployContract { code } => Self::DeployContract { code },
+            LegacyMultiSigRequestAction::AddKey {
+                public_key,
+                permission,
+            } => Self::AddKey {
+                public_key,
+                permission,
+            },
+            // In v1 `DeleteKey` could only target keys of the multisig itself, which are members now.
+            LegacyMultiSigRequestAction::DeleteKey { public_key } => Self::DeleteMember {
+                member: MultisigMember::AccessKey { public_key },
+            },
+            LegacyMultiSigRequestAction::FunctionCall {
+                method_name,
+                args,
+                deposit,
+                gas,
+            } => Self::FunctionCall {
+                method_name,
+                args,
+                deposit,
+                gas,
+            },
+            LegacyMultiSigRequestAction::SetNumConfirmations { num_confirmations } => {
+                Self::SetNumConfirmations { num_confirmations }
+            }
+            LegacyMultiSigRequestAction::SetActiveRequestsLimit {
+                active_requests_limit,
+            } => Self::SetActiveRequestsLimit {
+                active_requests_limit,
+            },
+        }
+    }
+}
+
+/// `MultiSigRequest` as stored by multisig v1.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct LegacyMultiSigRequest {
+    pub receiver_id: AccountId,
+    pub actions: Vec<LegacyMultiSigRequestAction>,
+}
+
+/// `MultiSigRequestWithSigner` as stored by multisig v1.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct LegacyMultiSigRequestWithSigner {
+    pub request: LegacyMultiSigRequest,
+    pub signer_pk: PublicKey,
+    pub added_timestamp: u64,
+}
+
+/// Contract state of multisig v1.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct LegacyMultiSigContract {
+    pub num_confirmations: u32,
+    pub request_nonce: RequestId,
+    pub requests: UnorderedMap<RequestId, LegacyMultiSigRequestWithSigner>,
+    pub confirmations: UnorderedMap<RequestId, HashSet<PublicKey>>,
+    pub num_requests_pk: UnorderedMap<PublicKey, u32>,
+    pub active_requests_limit: u32,
+}
+
+#[cfg(test)]
+impl LegacyMultiSigContract {
+    pub(crate) fn new(num_confirmations: u32) -> Self {
+        Self {
+            num_confirmations,
+            request_nonce: 0,
+            requests: UnorderedMap::new(b"r".to_vec()),
+            confirmations: UnorderedMap::new(b"c".to_vec()),
+            num_requests_pk: UnorderedMap::new(b"k".to_vec()),
+            active_requests_limit: ACTIVE_REQUESTS_LIMIT,
+        }
+    }
+}
+
+#[near_bindgen]
+impl MultiSigContract {
+    /// Migrates state of multisig v1 deployed on this account into multisig2.
+    /// Must be called by the account itself right after deploying the new code, e.g. as the last
+    /// action of the v1 request that deploys multisig2.
+    /// @params members: function call access keys of the account (as {"public_key": "key"}) and any
+    ///   additional account members. Keys that signed or confirmed pending requests are added as
+    ///   members automatically. Access keys themselves are left untouched.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate(members: Vec<MultisigMember>) -> Self {
+        let mut old: LegacyMultiSigContract =
+            env::state_read().unwrap_or_else(|| env::panic_str("No multisig v1 state to migrate"));
+        let mut multisig = Self {
+            members: UnorderedSet::new(StorageKeys::Members),
+            num_confirmations: old.num_confirmations,
+            request_nonce: old.request_nonce,
+            requests: UnorderedMap::new(StorageKeys::Requests),
+            confirmations: LookupMap::new(StorageKeys::Confirmations),
+            num_requests_pk: LookupMap::new(StorageKeys::NumRequestsPk),
+            active_requests_limit: old.active_requests_limit,
+        };
+        for member in members {
+            multisig.members.insert(&member);
+        }
+        for (request_id, request_with_signer) in old.requests.iter() {
+            let member = MultisigMember::AccessKey {
+                public_key: request_with_signer.signer_pk,
+            };
+            multisig.members.insert(&member);
+            let num_requests = multisig
+                .num_requests_pk
+                .get(&member.to_string())
+                .unwrap_or(0);
+            multisig
+                .num_requests_pk
+                .insert(&member.to_string(), &(num_requests + 1));
+            let confirmations: HashSet<String> = old
+                .confirmations
+                .get(&request_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|public_key| {
+                    let member = MultisigMember::AccessKey { public_key };
+                    multisig.members.insert(&member);
+                    member.to_string()
+                })
+                .collect();
+            multisig.confirmations.insert(&request_id, &confirmations);
+            multisig.requests.insert(
+                &request_id,
+                &MultiSigRequestWithSigner {
+                    request: MultiSigRequest {
+                        receiver_id: request_with_signer.request.receiver_id,
+                        actions: request_with_signer
+                            .request
+                            .actions
+                            .into_iter()
+                            .map(Into::into)
+                            .collect(),
+                    },
+                    member,
+                    added_timestamp: request_with_signer.added_timestamp,
+                },
+            );
+        }
+        assert(
+            multisig.members.len() >= multisig.num_confirmations as u64,
+            "Members list must be equal or larger than number of confirmations",
+        );
+        old.requests.clear();
+        old.confirmations.clear();
+        old.num_requests_pk.clear();
+        multisig
+    }
+}
@@ -12,7 +12,7 @@ await new Promise(resolve => setTimeout(resolve, 100));
 const fs = require('fs');
 const account = await near.account("$MASTER_ACCOUNT_ID");
 const contractName = "$ACCOUNT_ID";
-const newArgs = {staking_pool_whitelist_account_id: "$WHITELIST_ACCOUNT_ID"};
+const newArgs = {staking_pool_whitelist_account_id: "$WHITELIST_ACCOUNT_ID", owner_id: "$FOUNDATION_ACCOUNT_ID"};
 await account.signAndSendTransaction(
     contractName,
     [
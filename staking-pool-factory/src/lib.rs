@@ -1,6 +1,6 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::UnorderedSet;
-use near_sdk::json_types::{Base58PublicKey, U128};
+use near_sdk::collections::{LookupMap, UnorderedSet};
+use near_sdk::json_types::{Base58PublicKey, Base64VecU8, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, ext_contract, near_bindgen, AccountId, Balance, Promise, PromiseOrValue};
 
@@ -21,17 +21,28 @@ pub mod gas {
     pub const STAKING_POOL_NEW: Gas = BASE * 2;
 
     /// The amount of Gas the contract will attach to the callback to itself.
-    /// The base for the execution and the base for whitelist call or cash rollback.
-    pub const CALLBACK: Gas = BASE * 2;
+    /// The base for the execution and the base for whitelist call with its callback or cash
+    /// rollback.
+    pub const CALLBACK: Gas = BASE * 3;
 
     /// The amount of Gas the contract will attach to the promise to the whitelist contract.
     /// The base for the execution.
     pub const WHITELIST_STAKING_POOL: Gas = BASE;
+
+    /// The amount of Gas the contract will attach to the callback after whitelisting.
+    /// The base for the execution.
+    pub const ON_WHITELIST_CALLBACK: Gas = BASE;
 }
 
 /// There is no deposit balance attached.
 const NO_DEPOSIT: Balance = 0;
 
+/// Staking pool code that is used when no other code is set as default.
+const STAKING_POOL_CODE: &[u8] = include_bytes!("../../staking-pool/res/staking_pool.wasm");
+
+/// Base58 encoded sha256 hash of the staking pool code. Matches `code_hash` of the pool account.
+pub type CodeHash = String;
+
 #[global_allocator]
 static ALLOC: near_sdk::wee_alloc::WeeAlloc = near_sdk::wee_alloc::WeeAlloc::INIT;
 
@@ -43,6 +54,35 @@ pub struct StakingPoolFactory {
 
     /// The account ID of the staking pools created.
     staking_pool_account_ids: UnorderedSet<AccountId>,
+
+    /// Account ID that manages versions of the staking pool code.
+    owner_id: AccountId,
+
+    /// Hashes of the staking pool code approved for new pools and upgrades of existing pools.
+    code_hashes: UnorderedSet<CodeHash>,
+
+    /// Staking pool code per hash.
+    codes: LookupMap<CodeHash, Vec<u8>>,
+
+    /// Code used when creator doesn't specify one. Embedded code is used if not set.
+    default_code_hash: Option<CodeHash>,
+
+    /// Information recorded at creation per staking pool account ID.
+    staking_pool_infos: LookupMap<AccountId, StakingPoolInfo>,
+
+    /// Account IDs of the staking pools created per owner account ID.
+    staking_pools_by_owner: LookupMap<AccountId, Vec<AccountId>>,
+
+    /// The account IDs of the staking pools created, but not whitelisted yet. Either the
+    /// whitelisting is in progress or it has failed and should be retried.
+    not_whitelisted_staking_pool_account_ids: UnorderedSet<AccountId>,
+}
+
+/// State of the factory before staking pool code versions were introduced.
+#[derive(BorshDeserialize)]
+struct OldStakingPoolFactory {
+    staking_pool_whitelist_account_id: AccountId,
+    staking_pool_account_ids: UnorderedSet<AccountId>,
 }
 
 impl Default for StakingPoolFactory {
@@ -52,7 +92,7 @@ impl Default for StakingPoolFactory {
 }
 
 /// Rewards fee fraction structure for the staking pool contract.
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct RewardFeeFraction {
     pub numerator: u32,
@@ -69,6 +109,46 @@ impl RewardFeeFraction {
     }
 }
 
+/// Human-readable information about the staking pool. Matches `PoolMetadata` of the staking pool
+/// contract, which also validates the length limits of the fields.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub logo: Option<String>,
+    pub contact: Option<String>,
+    pub location: Option<String>,
+}
+
+/// Information about the staking pool recorded by the factory at creation.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakingPoolInfo {
+    /// The initial owner account ID of the staking pool.
+    pub owner_id: AccountId,
+    /// The block timestamp when the staking pool creation was requested.
+    pub created_timestamp: U64,
+    /// The initial reward fee fraction of the staking pool.
+    pub reward_fee_fraction: RewardFeeFraction,
+    /// The hash of the code deployed to the staking pool, `None` for the code embedded into the
+    /// factory.
+    pub code_hash: Option<CodeHash>,
+}
+
+/// Represents a staking pool created by the factory.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableStakingPool {
+    pub account_id: AccountId,
+    /// Information recorded at creation. Missing for pools created by older factory versions.
+    pub info: Option<StakingPoolInfo>,
+    /// Whether the staking pool was whitelisted by the factory.
+    /// It's `false` while whitelisting is in progress or after it has failed.
+    pub is_whitelisted: bool,
+}
+
 #[derive(Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct StakingPoolArgs {
@@ -78,6 +158,10 @@ pub struct StakingPoolArgs {
     stake_public_key: Base58PublicKey,
     /// The initial reward fee fraction.
     reward_fee_fraction: RewardFeeFraction,
+    /// The initial metadata of the pool. Omitted if not given, so older pool code still accepts
+    /// the arguments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<PoolMetadata>,
 }
 
 /// External interface for the callbacks to self.
@@ -89,6 +173,8 @@ pub trait ExtSelf {
         attached_deposit: U128,
         predecessor_account_id: AccountId,
     ) -> Promise;
+
+    fn on_staking_pool_whitelist(&mut self, staking_pool_account_id: AccountId) -> bool;
 }
 
 /// External interface for the whitelist contract.
@@ -100,18 +186,41 @@ pub trait ExtWhitelist {
 #[near_bindgen]
 impl StakingPoolFactory {
     /// Initializes the staking pool factory with the given account ID of the staking pool whitelist
-    /// contract.
+    /// contract and the account ID of the owner that manages staking pool code versions.
     #[init]
-    pub fn new(staking_pool_whitelist_account_id: AccountId) -> Self {
+    pub fn new(staking_pool_whitelist_account_id: AccountId, owner_id: AccountId) -> Self {
         assert!(!env::state_exists(), "The contract is already initialized");
         assert!(
             env::is_valid_account_id(staking_pool_whitelist_account_id.as_bytes()),
             "The staking pool whitelist account ID is invalid"
         );
-        Self {
+        assert!(
+            env::is_valid_account_id(owner_id.as_bytes()),
+            "The owner account ID is invalid"
+        );
+        Self::from_parts(
             staking_pool_whitelist_account_id,
-            staking_pool_account_ids: UnorderedSet::new(b"s".to_vec()),
-        }
+            UnorderedSet::new(b"s".to_vec()),
+            owner_id,
+        )
+    }
+
+    /// Migrates the state of the factory deployed before staking pool code versions were
+    /// introduced. Can only be called by the factory itself.
+    #[init]
+    pub fn migrate(owner_id: AccountId) -> Self {
+        assert_self();
+        assert!(
+            env::is_valid_account_id(owner_id.as_bytes()),
+            "The owner account ID is invalid"
+        );
+        let old: OldStakingPoolFactory =
+            env::state_read().expect("The contract is not initialized");
+        Self::from_parts(
+            old.staking_pool_whitelist_account_id,
+            old.staking_pool_account_ids,
+            owner_id,
+        )
     }
 
     /// Returns the minimum amount of tokens required to attach to the function call to
@@ -133,6 +242,10 @@ impl StakingPoolFactory {
     ///     pool.
     /// - `stake_public_key` - the initial staking key for the staking pool.
     /// - `reward_fee_fraction` - the initial reward fee fraction for the staking pool.
+    /// - `code_hash` - the hash of the approved staking pool code to deploy. The default code is
+    ///    used if not given.
+    /// - `metadata` - the initial human-readable information about the staking pool. The pool owner
+    ///    can update it later.
     #[payable]
     pub fn create_staking_pool(
         &mut self,
@@ -140,6 +253,8 @@ impl StakingPoolFactory {
         owner_id: AccountId,
         stake_public_key: Base58PublicKey,
         reward_fee_fraction: RewardFeeFraction,
+        code_hash: Option<CodeHash>,
+        metadata: Option<PoolMetadata>,
     ) -> Promise {
         assert!(
             env::attached_deposit() >= MIN_ATTACHED_BALANCE,
@@ -163,22 +278,38 @@ impl StakingPoolFactory {
         );
         reward_fee_fraction.assert_valid();
 
+        let code_hash = code_hash.or_else(|| self.default_code_hash.clone());
+        let code = match code_hash.as_ref() {
+            Some(code_hash) => self.internal_get_code(code_hash),
+            None => STAKING_POOL_CODE.to_vec(),
+        };
+
         assert!(
             self.staking_pool_account_ids
                 .insert(&staking_pool_account_id),
             "The staking pool account ID already exists"
         );
+        self.internal_add_staking_pool_info(
+            &staking_pool_account_id,
+            StakingPoolInfo {
+                owner_id: owner_id.clone(),
+                created_timestamp: env::block_timestamp().into(),
+                reward_fee_fraction: reward_fee_fraction.clone(),
+                code_hash,
+            },
+        );
 
         Promise::new(staking_pool_account_id.clone())
             .create_account()
             .transfer(env::attached_deposit())
-            .deploy_contract(include_bytes!("../../staking-pool/res/staking_pool.wasm").to_vec())
+            .deploy_contract(code)
             .function_call(
                 b"new".to_vec(),
                 near_sdk::serde_json::to_vec(&StakingPoolArgs {
                     owner_id,
                     stake_public_key,
                     reward_fee_fraction,
+                    metadata,
                 })
                 .unwrap(),
                 NO_DEPOSIT,
@@ -196,6 +327,7 @@ impl StakingPoolFactory {
 
     /// Callback after a staking pool was created.
     /// Returns the promise to whitelist the staking pool contract if the pool creation succeeded.
+    /// The result of whitelisting is checked by `on_staking_pool_whitelist`.
     /// Otherwise refunds the attached deposit and returns `false`.
     pub fn on_staking_pool_create(
         &mut self,
@@ -215,16 +347,14 @@ impl StakingPoolFactory {
                 )
                 .as_bytes(),
             );
-            ext_whitelist::add_staking_pool(
-                staking_pool_account_id,
-                &self.staking_pool_whitelist_account_id,
-                NO_DEPOSIT,
-                gas::WHITELIST_STAKING_POOL,
-            )
-            .into()
+            self.not_whitelisted_staking_pool_account_ids
+                .insert(&staking_pool_account_id);
+            self.internal_whitelist_staking_pool(staking_pool_account_id)
+                .into()
         } else {
             self.staking_pool_account_ids
                 .remove(&staking_pool_account_id);
+            self.internal_remove_staking_pool_info(&staking_pool_account_id);
             env::log(
                 format!(
                     "The staking pool @{} creation has failed. Returning attached deposit of {} to @{}",
@@ -237,6 +367,252 @@ impl StakingPoolFactory {
             PromiseOrValue::Value(false)
         }
     }
+
+    /// Callback after the whitelisting of the staking pool.
+    /// Records whether the staking pool was whitelisted and returns it.
+    pub fn on_staking_pool_whitelist(&mut self, staking_pool_account_id: AccountId) -> bool {
+        assert_self();
+
+        let staking_pool_whitelisted = is_promise_success();
+
+        if staking_pool_whitelisted {
+            self.not_whitelisted_staking_pool_account_ids
+                .remove(&staking_pool_account_id);
+            env::log(
+                format!(
+                    "The staking pool @{} was successfully whitelisted.",
+                    staking_pool_account_id
+                )
+                .as_bytes(),
+            );
+        } else {
+            self.not_whitelisted_staking_pool_account_ids
+                .insert(&staking_pool_account_id);
+            env::log(
+                format!(
+                    "The staking pool @{} whitelisting has failed. Call `whitelist_staking_pool` to retry.",
+                    staking_pool_account_id
+                )
+                .as_bytes(),
+            );
+        }
+        staking_pool_whitelisted
+    }
+
+    /// Retries whitelisting of the staking pool created by this factory which whitelisting has
+    /// failed. Can be called by anyone.
+    /// The pools created by older versions of the factory that didn't track whitelisting can only
+    /// be retried by the owner. Such pool is tracked as not whitelisted until the retry succeeds.
+    pub fn whitelist_staking_pool(&mut self, staking_pool_account_id: AccountId) -> Promise {
+        if !self
+            .not_whitelisted_staking_pool_account_ids
+            .contains(&staking_pool_account_id)
+        {
+            assert!(
+                self.staking_pool_account_ids
+                    .contains(&staking_pool_account_id)
+                    && self
+                        .staking_pool_infos
+                        .get(&staking_pool_account_id)
+                        .is_none(),
+                "The staking pool is not created by this factory or is already whitelisted"
+            );
+            self.assert_owner();
+            self.not_whitelisted_staking_pool_account_ids
+                .insert(&staking_pool_account_id);
+        }
+        self.internal_whitelist_staking_pool(staking_pool_account_id)
+    }
+
+    /// Returns the list of the staking pools created from this factory, but not whitelisted,
+    /// starting from the given index up to the given limit.
+    pub fn get_not_whitelisted_staking_pools(&self, from_index: u64, limit: u64) -> Vec<AccountId> {
+        let account_ids = self.not_whitelisted_staking_pool_account_ids.as_vector();
+
+        (from_index..std::cmp::min(from_index.saturating_add(limit), account_ids.len()))
+            .map(|index| account_ids.get(index).unwrap())
+            .collect()
+    }
+
+    /// Returns the list of the staking pools created from this factory, starting from the given
+    /// index up to the given limit.
+    pub fn get_staking_pools(&self, from_index: u64, limit: u64) -> Vec<HumanReadableStakingPool> {
+        let account_ids = self.staking_pool_account_ids.as_vector();
+
+        (from_index..std::cmp::min(from_index.saturating_add(limit), account_ids.len()))
+            .map(|index| self.get_staking_pool(account_ids.get(index).unwrap()))
+            .collect()
+    }
+
+    /// Returns the staking pool created from this factory for the given account ID.
+    pub fn get_staking_pool(&self, staking_pool_account_id: AccountId) -> HumanReadableStakingPool {
+        assert!(
+            self.staking_pool_account_ids
+                .contains(&staking_pool_account_id),
+            "The staking pool is not created by this factory"
+        );
+        HumanReadableStakingPool {
+            info: self.staking_pool_infos.get(&staking_pool_account_id),
+            is_whitelisted: !self
+                .not_whitelisted_staking_pool_account_ids
+                .contains(&staking_pool_account_id),
+            account_id: staking_pool_account_id,
+        }
+    }
+
+    /// Returns account IDs of the staking pools created with the given owner account ID,
+    /// starting from the given index up to the given limit.
+    pub fn get_staking_pools_by_owner(
+        &self,
+        owner_id: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<AccountId> {
+        self.staking_pools_by_owner
+            .get(&owner_id)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Returns the account ID of the owner that manages staking pool code versions.
+    pub fn get_owner_id(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// Returns hashes of the approved staking pool code versions.
+    pub fn get_code_hashes(&self) -> Vec<CodeHash> {
+        self.code_hashes.to_vec()
+    }
+
+    /// Returns the hash of the code used for new staking pools by default.
+    /// `None` means the code embedded into the factory is used.
+    pub fn get_default_code_hash(&self) -> Option<CodeHash> {
+        self.default_code_hash.clone()
+    }
+
+    /// Returns raw (not JSON encoded) staking pool code for the given hash.
+    /// Used by the existing staking pools to upgrade to the approved code.
+    pub fn get_code(&self, code_hash: CodeHash) {
+        env::value_return(&self.internal_get_code(&code_hash));
+    }
+
+    /// Owner's method.
+    /// Approves new staking pool code for new pools and upgrades of existing pools.
+    /// Returns the hash of the code.
+    pub fn add_code(&mut self, code: Base64VecU8) -> CodeHash {
+        self.assert_owner();
+        let code: Vec<u8> = code.into();
+        let code_hash = bs58::encode(env::sha256(&code)).into_string();
+        assert!(
+            self.code_hashes.insert(&code_hash),
+            "The code is already added"
+        );
+        self.codes.insert(&code_hash, &code);
+        code_hash
+    }
+
+    /// Owner's method.
+    /// Removes the staking pool code with the given hash.
+    pub fn remove_code(&mut self, code_hash: CodeHash) {
+        self.assert_owner();
+        assert!(self.code_hashes.remove(&code_hash), "No such code");
+        self.codes.remove(&code_hash);
+        if self.default_code_hash.as_ref() == Some(&code_hash) {
+            self.default_code_hash = None;
+        }
+    }
+
+    /// Owner's method.
+    /// Sets the code used for new staking pools by default. The embedded code is used if `None`.
+    pub fn set_default_code_hash(&mut self, code_hash: Option<CodeHash>) {
+        self.assert_owner();
+        if let Some(code_hash) = code_hash.as_ref() {
+            assert!(self.code_hashes.contains(code_hash), "No such code");
+        }
+        self.default_code_hash = code_hash;
+    }
+}
+
+impl StakingPoolFactory {
+    fn from_parts(
+        staking_pool_whitelist_account_id: AccountId,
+        staking_pool_account_ids: UnorderedSet<AccountId>,
+        owner_id: AccountId,
+    ) -> Self {
+        Self {
+            staking_pool_whitelist_account_id,
+            staking_pool_account_ids,
+            owner_id,
+            code_hashes: UnorderedSet::new(b"h".to_vec()),
+            codes: LookupMap::new(b"c".to_vec()),
+            default_code_hash: None,
+            staking_pool_infos: LookupMap::new(b"i".to_vec()),
+            staking_pools_by_owner: LookupMap::new(b"o".to_vec()),
+            not_whitelisted_staking_pool_account_ids: UnorderedSet::new(b"w".to_vec()),
+        }
+    }
+
+    fn internal_whitelist_staking_pool(&self, staking_pool_account_id: AccountId) -> Promise {
+        ext_whitelist::add_staking_pool(
+            staking_pool_account_id.clone(),
+            &self.staking_pool_whitelist_account_id,
+            NO_DEPOSIT,
+            gas::WHITELIST_STAKING_POOL,
+        )
+        .then(ext_self::on_staking_pool_whitelist(
+            staking_pool_account_id,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            gas::ON_WHITELIST_CALLBACK,
+        ))
+    }
+
+    fn internal_add_staking_pool_info(
+        &mut self,
+        staking_pool_account_id: &AccountId,
+        info: StakingPoolInfo,
+    ) {
+        let mut account_ids = self
+            .staking_pools_by_owner
+            .get(&info.owner_id)
+            .unwrap_or_default();
+        account_ids.push(staking_pool_account_id.clone());
+        self.staking_pools_by_owner
+            .insert(&info.owner_id, &account_ids);
+        self.staking_pool_infos
+            .insert(staking_pool_account_id, &info);
+    }
+
+    fn internal_remove_staking_pool_info(&mut self, staking_pool_account_id: &AccountId) {
+        if let Some(info) = self.staking_pool_infos.remove(staking_pool_account_id) {
+            let mut account_ids = self
+                .staking_pools_by_owner
+                .get(&info.owner_id)
+                .unwrap_or_default();
+            account_ids.retain(|account_id| account_id != staking_pool_account_id);
+            if account_ids.is_empty() {
+                self.staking_pools_by_owner.remove(&info.owner_id);
+            } else {
+                self.staking_pools_by_owner
+                    .insert(&info.owner_id, &account_ids);
+            }
+        }
+    }
+
+    fn assert_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Can only be called by the owner"
+        );
+    }
+
+    fn internal_get_code(&self, code_hash: &CodeHash) -> Vec<u8> {
+        self.codes.get(code_hash).expect("No such code")
+    }
 }
 
 #[cfg(test)]
@@ -256,7 +632,7 @@ mod tests {
             .finish();
         testing_env!(context.clone());
 
-        let mut contract = StakingPoolFactory::new(account_whitelist());
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
 
         context.is_view = true;
         testing_env!(context.clone());
@@ -277,6 +653,8 @@ mod tests {
                 numerator: 10,
                 denominator: 100,
             },
+            None,
+            None,
         );
 
         context.predecessor_account_id = account_factory();
@@ -287,6 +665,156 @@ mod tests {
         context.is_view = true;
         testing_env!(context.clone());
         assert_eq!(contract.get_number_of_staking_pools_created(), 1);
+        let staking_pools = contract.get_staking_pools(0, 10);
+        assert_eq!(staking_pools.len(), 1);
+        assert_eq!(staking_pools[0].account_id, account_pool());
+        let info = staking_pools[0].info.clone().unwrap();
+        assert_eq!(info.owner_id, account_pool_owner());
+        assert_eq!(info.reward_fee_fraction.numerator, 10);
+        assert_eq!(info.code_hash, None);
+        assert_eq!(
+            contract.get_staking_pools_by_owner(account_pool_owner(), 0, 10),
+            vec![account_pool()]
+        );
+        assert!(contract
+            .get_staking_pools_by_owner(account_pool_owner(), 1, 10)
+            .is_empty());
+        assert_eq!(contract.get_staking_pools(0, u64::MAX).len(), 1);
+        assert!(contract.get_staking_pools(1, 10).is_empty());
+        assert!(!staking_pools[0].is_whitelisted);
+        assert_eq!(
+            contract.get_not_whitelisted_staking_pools(0, 10),
+            vec![account_pool()]
+        );
+        assert_eq!(
+            contract.get_not_whitelisted_staking_pools(0, u64::MAX),
+            vec![account_pool()]
+        );
+
+        context.is_view = false;
+        testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
+        assert!(contract.on_staking_pool_whitelist(account_pool()));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert!(contract.get_staking_pool(account_pool()).is_whitelisted);
+        assert!(contract.get_not_whitelisted_staking_pools(0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_whitelist_staking_pool_retry() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
+
+        context.predecessor_account_id = account_tokens_owner();
+        context.attached_deposit = ntoy(31);
+        testing_env!(context.clone());
+        contract.create_staking_pool(
+            staking_pool_id(),
+            account_pool_owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7"
+                .try_into()
+                .unwrap(),
+            RewardFeeFraction {
+                numerator: 10,
+                denominator: 100,
+            },
+            None,
+            None,
+        );
+
+        context.predecessor_account_id = account_factory();
+        context.attached_deposit = ntoy(0);
+        testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
+        contract.on_staking_pool_create(account_pool(), ntoy(31).into(), account_tokens_owner());
+        testing_env_with_promise_results(context.clone(), PromiseResult::Failed);
+        assert!(!contract.on_staking_pool_whitelist(account_pool()));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert_eq!(
+            contract.get_not_whitelisted_staking_pools(0, 10),
+            vec![account_pool()]
+        );
+
+        // Anyone can retry whitelisting.
+        context.is_view = false;
+        context.predecessor_account_id = account_near();
+        testing_env!(context.clone());
+        contract.whitelist_staking_pool(account_pool());
+
+        context.predecessor_account_id = account_factory();
+        testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
+        assert!(contract.on_staking_pool_whitelist(account_pool()));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert!(contract.get_not_whitelisted_staking_pools(0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_whitelist_legacy_staking_pool() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
+        // The pool created by an older version of the factory.
+        contract.staking_pool_account_ids.insert(&account_pool());
+
+        contract.whitelist_staking_pool(account_pool());
+        assert_eq!(
+            contract.get_not_whitelisted_staking_pools(0, 10),
+            vec![account_pool()]
+        );
+
+        context.predecessor_account_id = account_factory();
+        testing_env_with_promise_results(context.clone(), PromiseResult::Successful(vec![]));
+        assert!(contract.on_staking_pool_whitelist(account_pool()));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert!(contract.get_not_whitelisted_staking_pools(0, 10).is_empty());
+        assert!(contract.get_staking_pool(account_pool()).is_whitelisted);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only be called by the owner")]
+    fn test_whitelist_legacy_staking_pool_not_owner() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
+        contract.staking_pool_account_ids.insert(&account_pool());
+
+        context.predecessor_account_id = account_tokens_owner();
+        testing_env!(context.clone());
+        contract.whitelist_staking_pool(account_pool());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The staking pool is not created by this factory or is already whitelisted"
+    )]
+    fn test_whitelist_staking_pool_unknown() {
+        let context = VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
+        contract.whitelist_staking_pool(account_pool());
     }
 
     #[test]
@@ -298,7 +826,7 @@ mod tests {
             .finish();
         testing_env!(context.clone());
 
-        let mut contract = StakingPoolFactory::new(account_whitelist());
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
 
         // Checking the pool is still whitelisted
         context.is_view = true;
@@ -320,6 +848,8 @@ mod tests {
                 numerator: 10,
                 denominator: 100,
             },
+            None,
+            None,
         );
     }
 
@@ -331,7 +861,7 @@ mod tests {
             .finish();
         testing_env!(context.clone());
 
-        let mut contract = StakingPoolFactory::new(account_whitelist());
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
 
         context.is_view = true;
         testing_env!(context.clone());
@@ -352,6 +882,8 @@ mod tests {
                 numerator: 10,
                 denominator: 100,
             },
+            None,
+            None,
         );
 
         context.predecessor_account_id = account_factory();
@@ -371,5 +903,107 @@ mod tests {
         context.is_view = true;
         testing_env!(context.clone());
         assert_eq!(contract.get_number_of_staking_pools_created(), 0);
+        assert!(contract
+            .get_staking_pools_by_owner(account_pool_owner(), 0, 10)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_create_staking_pool_with_code_hash() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
+        let code_hash = contract.add_code(vec![1, 2, 3].into());
+        assert_eq!(
+            code_hash,
+            bs58::encode(env::sha256(&[1, 2, 3])).into_string()
+        );
+        contract.set_default_code_hash(Some(code_hash.clone()));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert_eq!(contract.get_code_hashes(), vec![code_hash.clone()]);
+        assert_eq!(contract.get_default_code_hash(), Some(code_hash.clone()));
+
+        context.is_view = false;
+        context.predecessor_account_id = account_tokens_owner();
+        context.attached_deposit = ntoy(31);
+        testing_env!(context.clone());
+        contract.create_staking_pool(
+            staking_pool_id(),
+            account_pool_owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7"
+                .try_into()
+                .unwrap(),
+            RewardFeeFraction {
+                numerator: 10,
+                denominator: 100,
+            },
+            Some(code_hash.clone()),
+            None,
+        );
+        assert_eq!(contract.get_number_of_staking_pools_created(), 1);
+
+        context.predecessor_account_id = account_near();
+        context.attached_deposit = ntoy(0);
+        testing_env!(context.clone());
+        contract.remove_code(code_hash);
+        assert!(contract.get_code_hashes().is_empty());
+        assert_eq!(contract.get_default_code_hash(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "No such code")]
+    fn test_create_staking_pool_unknown_code_hash() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(account_tokens_owner())
+            .finish();
+        context.attached_deposit = ntoy(31);
+        testing_env!(context.clone());
+
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
+        contract.create_staking_pool(
+            staking_pool_id(),
+            account_pool_owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7"
+                .try_into()
+                .unwrap(),
+            RewardFeeFraction {
+                numerator: 10,
+                denominator: 100,
+            },
+            Some("unknown".to_string()),
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only be called by the owner")]
+    fn test_add_code_not_owner() {
+        let context = VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(account_tokens_owner())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = StakingPoolFactory::new(account_whitelist(), account_near());
+        contract.add_code(vec![1, 2, 3].into());
+    }
+
+    #[test]
+    #[should_panic(expected = "The owner account ID is invalid")]
+    fn test_new_invalid_owner() {
+        let context = VMContextBuilder::new()
+            .current_account_id(account_factory())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context);
+
+        StakingPoolFactory::new(account_whitelist(), "Invalid Owner".to_string());
     }
 }
@@ -140,7 +140,11 @@ impl ExternalUser {
             .deploy_contract(FACTORY_WASM_BYTES.to_vec())
             .function_call(
                 "new".into(),
-                serde_json::to_vec(&json!({"staking_pool_whitelist_account_id": staking_pool_whitelist_account_id.to_string()})).unwrap(),
+                serde_json::to_vec(&json!({
+                    "staking_pool_whitelist_account_id": staking_pool_whitelist_account_id.to_string(),
+                    "owner_id": self.account_id(),
+                }))
+                .unwrap(),
                 MAX_GAS,
                 0,
             )
@@ -155,6 +155,8 @@ impl StakingContract {
         account.unstaked += receive_amount;
         account.unstaked_available_epoch_height = env::epoch_height() + NUM_EPOCHS_TO_UNLOCK;
         self.internal_save_account(&account_id, &account);
+        // The unstaked balance can't be used for votes anymore.
+        self.internal_lower_delegator_votes(&account_id, account.stake_shares);
 
         // The amount tokens that will be unstaked from the total to guarantee the "stake" share
         // price never decreases. The difference between `receive_amount` and `unstake_amount` is
@@ -334,4 +336,132 @@ impl StakingContract {
             self.accounts.remove(account_id);
         }
     }
+
+    /// Inner method to get the pool metadata or the empty default value.
+    pub(crate) fn internal_get_pool_metadata(&self) -> PoolMetadata {
+        env::storage_read(POOL_METADATA_KEY)
+            .map(|data| {
+                PoolMetadata::try_from_slice(&data).expect("Failed to deserialize pool metadata")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Inner method to validate and save the pool metadata.
+    pub(crate) fn internal_set_pool_metadata(&mut self, metadata: &PoolMetadata) {
+        metadata.assert_valid();
+        env::storage_write(
+            POOL_METADATA_KEY,
+            &metadata
+                .try_to_vec()
+                .expect("Failed to serialize pool metadata"),
+        );
+    }
+
+    /// Inner method to remove the vote of the delegator on the given proposal of the voting
+    /// contract and subtract it from the totals. Returns the removed vote.
+    pub(crate) fn internal_remove_delegator_vote(
+        &mut self,
+        voting_account_id: &AccountId,
+        proposal_id: u64,
+        account_id: &AccountId,
+    ) -> Option<DelegatorVote> {
+        let delegator_vote = delegator_votes().remove(&(
+            voting_account_id.clone(),
+            proposal_id,
+            account_id.clone(),
+        ))?;
+        let mut voted_proposals = delegator_voted_proposals()
+            .get(account_id)
+            .unwrap_or_default();
+        voted_proposals.retain(|key| key != &(voting_account_id.clone(), proposal_id));
+        if voted_proposals.is_empty() {
+            delegator_voted_proposals().remove(account_id);
+        } else {
+            delegator_voted_proposals().insert(account_id, &voted_proposals);
+        }
+        let totals_key = (voting_account_id.clone(), proposal_id);
+        let mut totals = delegator_vote_totals()
+            .get(&totals_key)
+            .expect("Missing totals of delegator votes");
+        totals.remove(&delegator_vote);
+        if totals.num_votes == 0 {
+            delegator_vote_totals().remove(&totals_key);
+        } else {
+            delegator_vote_totals().insert(&totals_key, &totals);
+        }
+        Some(delegator_vote)
+    }
+
+    /// Inner method to save the vote of the delegator on the given proposal of the voting
+    /// contract and add it to the totals. The previous vote should be removed first.
+    pub(crate) fn internal_save_delegator_vote(
+        &mut self,
+        voting_account_id: &AccountId,
+        proposal_id: u64,
+        account_id: &AccountId,
+        delegator_vote: &DelegatorVote,
+    ) {
+        let totals_key = (voting_account_id.clone(), proposal_id);
+        let mut totals = delegator_vote_totals().get(&totals_key).unwrap_or_default();
+        if let Some(previous_vote) = delegator_votes().insert(
+            &(voting_account_id.clone(), proposal_id, account_id.clone()),
+            delegator_vote,
+        ) {
+            totals.remove(&previous_vote);
+        } else {
+            let mut voted_proposals = delegator_voted_proposals()
+                .get(account_id)
+                .unwrap_or_default();
+            voted_proposals.push(totals_key.clone());
+            delegator_voted_proposals().insert(account_id, &voted_proposals);
+        }
+        totals.add(delegator_vote);
+        delegator_vote_totals().insert(&totals_key, &totals);
+    }
+
+    /// Inner method to lower the weight of all votes of the delegator to the given number of
+    /// "stake" shares.
+    pub(crate) fn internal_lower_delegator_votes(
+        &mut self,
+        account_id: &AccountId,
+        stake_shares: NumStakeShares,
+    ) {
+        for (voting_account_id, proposal_id) in delegator_voted_proposals()
+            .get(account_id)
+            .unwrap_or_default()
+        {
+            let mut delegator_vote = delegator_votes()
+                .get(&(voting_account_id.clone(), proposal_id, account_id.clone()))
+                .expect("Missing delegator vote");
+            if delegator_vote.stake_shares > stake_shares {
+                delegator_vote.stake_shares = stake_shares;
+                self.internal_save_delegator_vote(
+                    &voting_account_id,
+                    proposal_id,
+                    account_id,
+                    &delegator_vote,
+                );
+            }
+        }
+    }
+}
+
+/// Votes of delegators keyed by the voting account ID, the proposal ID and the delegator account ID.
+pub(crate) fn delegator_votes() -> LookupMap<(AccountId, u64, AccountId), DelegatorVote> {
+    LookupMap::new(DELEGATOR_VOTES_PREFIX.to_vec())
+}
+
+/// Totals of delegator votes keyed by the voting account ID and the proposal ID.
+pub(crate) fn delegator_vote_totals() -> LookupMap<(AccountId, u64), DelegatorVoteTotals> {
+    LookupMap::new(DELEGATOR_VOTE_TOTALS_PREFIX.to_vec())
+}
+
+/// The voting account IDs and the proposal IDs each delegator voted on.
+pub(crate) fn delegator_voted_proposals() -> LookupMap<AccountId, Vec<(AccountId, u64)>> {
+    LookupMap::new(DELEGATOR_VOTED_PROPOSALS_PREFIX.to_vec())
+}
+
+/// The voting account IDs whose transfers proposal the owner voted for.
+pub(crate) fn owner_votes() -> LookupSet<AccountId> {
+    LookupSet::new(OWNER_VOTES_PREFIX.to_vec())
 }
@@ -1,7 +1,7 @@
 use std::convert::TryInto;
 
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::UnorderedMap;
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap};
 use near_sdk::json_types::{Base58PublicKey, U128};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
@@ -12,12 +12,24 @@ use uint::construct_uint;
 
 mod internal;
 
+use internal::{delegator_vote_totals, delegator_voted_proposals, delegator_votes, owner_votes};
+
 /// The amount of gas given to complete `vote` call.
 const VOTE_GAS: u64 = 100_000_000_000_000;
 
 /// The amount of gas given to complete internal `on_stake_action` call.
 const ON_STAKE_ACTION_GAS: u64 = 20_000_000_000_000;
 
+/// The amount of gas given to the factory to return the code for the upgrade.
+const GET_CODE_GAS: u64 = 50_000_000_000_000;
+
+/// The amount of gas given to complete `migrate` call after the new code is deployed.
+const MIGRATE_GAS: u64 = 50_000_000_000_000;
+
+/// The amount of gas given to complete internal `on_get_code` call.
+/// It deploys the code received from the factory and calls `migrate`.
+const ON_GET_CODE_GAS: u64 = 50_000_000_000_000 + MIGRATE_GAS;
+
 /// The amount of yocto NEAR the contract dedicates to guarantee that the "share" price never
 /// decreases. It's used during rounding errors for share -> amount conversions.
 const STAKE_SHARE_PRICE_GUARANTEE_FUND: Balance = 1_000_000_000_000;
@@ -25,6 +37,43 @@ const STAKE_SHARE_PRICE_GUARANTEE_FUND: Balance = 1_000_000_000_000;
 /// There is no deposit balance attached.
 const NO_DEPOSIT: Balance = 0;
 
+/// The storage key of the pool metadata. It's stored separately from the contract state, so the
+/// layout of the state is not affected.
+const POOL_METADATA_KEY: &[u8] = b"m";
+
+/// The storage prefix of delegator votes on proposals of voting contracts. Like the pool metadata,
+/// they are stored separately from the contract state.
+const DELEGATOR_VOTES_PREFIX: &[u8] = b"v";
+
+/// The storage prefix of the totals of delegator votes per proposal of voting contracts.
+const DELEGATOR_VOTE_TOTALS_PREFIX: &[u8] = b"t";
+
+/// The storage prefix of the proposals each delegator voted on. Used to lower the weight of the
+/// votes when the delegator unstakes.
+const DELEGATOR_VOTED_PROPOSALS_PREFIX: &[u8] = b"p";
+
+/// The storage prefix of the voting contracts whose transfers proposal the owner voted for.
+const OWNER_VOTES_PREFIX: &[u8] = b"o";
+
+/// The maximum number of proposals a delegator can vote on at the same time.
+const MAX_DELEGATOR_VOTES: usize = 16;
+
+/// The minimum staked balance of a delegator to vote on proposals. 1 NEAR.
+const MIN_DELEGATOR_VOTE_STAKE: Balance = 1_000_000_000_000_000_000_000_000;
+
+/// The price of a byte of storage paid by delegators for their votes.
+const STORAGE_PRICE_PER_BYTE: Balance = 10_000_000_000_000_000_000;
+
+/// The proposal of the voting contract that `vote` is called for.
+const TRANSFERS_PROPOSAL_ID: u64 = 0;
+
+/// The maximum length in bytes of the pool metadata fields.
+const MAX_POOL_NAME_LEN: usize = 64;
+const MAX_POOL_DESCRIPTION_LEN: usize = 512;
+const MAX_POOL_URL_LEN: usize = 256;
+const MAX_POOL_CONTACT_LEN: usize = 128;
+const MAX_POOL_LOCATION_LEN: usize = 64;
+
 /// A type to distinguish between a balance and "stake" shares for better readability.
 pub type NumStakeShares = Balance;
 
@@ -143,12 +192,115 @@ impl RewardFeeFraction {
     }
 }
 
+/// Human-readable information about the staking pool set by the owner.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolMetadata {
+    /// The display name of the pool.
+    pub name: Option<String>,
+    /// The description of the pool.
+    pub description: Option<String>,
+    /// The website URL of the pool.
+    pub website: Option<String>,
+    /// The logo of the pool: either URL or hash of the image.
+    pub logo: Option<String>,
+    /// The contact of the pool owner, e.g. email.
+    pub contact: Option<String>,
+    /// The location of the validator node, e.g. country.
+    pub location: Option<String>,
+}
+
+impl PoolMetadata {
+    pub fn assert_valid(&self) {
+        assert_max_len(&self.name, MAX_POOL_NAME_LEN, "name");
+        assert_max_len(&self.description, MAX_POOL_DESCRIPTION_LEN, "description");
+        assert_max_len(&self.website, MAX_POOL_URL_LEN, "website");
+        assert_max_len(&self.logo, MAX_POOL_URL_LEN, "logo");
+        assert_max_len(&self.contact, MAX_POOL_CONTACT_LEN, "contact");
+        assert_max_len(&self.location, MAX_POOL_LOCATION_LEN, "location");
+    }
+}
+
+fn assert_max_len(value: &Option<String>, max_len: usize, field: &str) {
+    if let Some(value) = value {
+        assert!(
+            value.len() <= max_len,
+            "The pool metadata {} can't be longer than {} bytes",
+            field,
+            max_len
+        );
+    }
+}
+
+/// The vote of a delegator on a proposal of a voting contract.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DelegatorVote {
+    /// Votes for the proposal if `true`, against if `false`.
+    pub vote: bool,
+    /// The amount of "stake" shares of the delegator when the vote was cast, lowered when the
+    /// delegator unstakes.
+    pub stake_shares: NumStakeShares,
+    /// The deposit paid by the delegator for the storage of the vote. Refunded when the vote is
+    /// withdrawn.
+    pub storage_deposit: Balance,
+}
+
+/// The totals of delegator votes on a proposal of a voting contract.
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct DelegatorVoteTotals {
+    pub yes_stake_shares: NumStakeShares,
+    pub no_stake_shares: NumStakeShares,
+    pub num_votes: u64,
+}
+
+impl DelegatorVoteTotals {
+    pub fn add(&mut self, vote: &DelegatorVote) {
+        if vote.vote {
+            self.yes_stake_shares += vote.stake_shares;
+        } else {
+            self.no_stake_shares += vote.stake_shares;
+        }
+        self.num_votes += 1;
+    }
+
+    pub fn remove(&mut self, vote: &DelegatorVote) {
+        if vote.vote {
+            self.yes_stake_shares -= vote.stake_shares;
+        } else {
+            self.no_stake_shares -= vote.stake_shares;
+        }
+        self.num_votes -= 1;
+    }
+}
+
+/// Represents the totals of delegator votes readable by humans.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableDelegatorVoteTotals {
+    /// The staked balance of delegators who voted for the proposal.
+    pub yes: U128,
+    /// The staked balance of delegators who voted against the proposal.
+    pub no: U128,
+    pub num_votes: u64,
+}
+
+/// The vote on a proposal of the voting contract.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalVote {
+    /// Splits the stake of the pool between yes, no and abstain pro rata to the given weights.
+    Split { yes: U128, no: U128, abstain: U128 },
+}
+
 /// Interface for a voting contract.
 #[ext_contract(ext_voting)]
 pub trait VoteContract {
     /// Method for validators to vote or withdraw the vote.
     /// Votes for if `is_vote` is true, or withdraws the vote if `is_vote` is false.
     fn vote(&mut self, is_vote: bool);
+
+    /// Method for validators to vote on the given proposal.
+    fn vote_on_proposal(&mut self, proposal_id: u64, vote: ProposalVote);
 }
 
 /// Interface for the contract itself.
@@ -160,6 +312,16 @@ pub trait SelfContract {
     /// follow withdraw calls might fail. To mitigate this, the contract will issue a new unstaking
     /// action in case of the failure of the first staking action.
     fn on_stake_action(&mut self);
+
+    /// A callback to deploy the code received from the factory and migrate the state.
+    fn on_get_code(&mut self, code_hash: String);
+}
+
+/// Interface for the staking pool factory that created this pool.
+#[ext_contract(ext_factory)]
+pub trait FactoryContract {
+    /// Returns raw code of the staking pool approved by the factory for the given hash.
+    fn get_code(&self, code_hash: String);
 }
 
 #[near_bindgen]
@@ -170,11 +332,14 @@ impl StakingContract {
     /// The entire current balance of this contract will be used to stake. This allows contract to
     /// always maintain staking shares that can't be unstaked or withdrawn.
     /// It prevents inflating the price of the share too much.
+    ///
+    /// The optional `metadata` sets the initial human-readable information about the pool.
     #[init]
     pub fn new(
         owner_id: AccountId,
         stake_public_key: Base58PublicKey,
         reward_fee_fraction: RewardFeeFraction,
+        metadata: Option<PoolMetadata>,
     ) -> Self {
         assert!(!env::state_exists(), "Already initialized");
         reward_fee_fraction.assert_valid();
@@ -200,6 +365,9 @@ impl StakingContract {
             accounts: UnorderedMap::new(b"u".to_vec()),
             paused: false,
         };
+        if let Some(metadata) = metadata {
+            this.internal_set_pool_metadata(&metadata);
+        }
         // Staking with the current pool to make sure the staking key is valid.
         this.internal_restake();
         this
@@ -313,6 +481,123 @@ impl StakingContract {
         self.internal_restake();
     }
 
+    /// Sets the preference of the predecessor on the given proposal of the voting contract.
+    /// Votes for if `vote` is true, against if it's false, or withdraws the vote if it's `None`.
+    /// The weight of the vote is the "stake" shares of the delegator at the time of the vote.
+    /// Voting again updates the weight, unstaking lowers it.
+    /// A new vote requires the staked balance of at least `MIN_DELEGATOR_VOTE_STAKE` and the
+    /// attached deposit to cover the storage of the vote. The remaining deposit is refunded.
+    /// A delegator can vote on at most `MAX_DELEGATOR_VOTES` proposals at the same time.
+    /// The storage deposit is refunded when the vote is withdrawn.
+    /// The votes of delegators are sent to the voting contract with `forward_delegator_votes`.
+    #[payable]
+    pub fn delegator_vote(
+        &mut self,
+        voting_account_id: AccountId,
+        proposal_id: u64,
+        vote: Option<bool>,
+    ) {
+        assert!(
+            env::is_valid_account_id(voting_account_id.as_bytes()),
+            "Invalid voting account ID"
+        );
+
+        let account_id = env::predecessor_account_id();
+        let mut refund = env::attached_deposit();
+        let initial_storage_usage = env::storage_usage();
+        let previous_vote =
+            self.internal_remove_delegator_vote(&voting_account_id, proposal_id, &account_id);
+        if let Some(vote) = vote {
+            assert!(
+                previous_vote.is_some()
+                    || delegator_voted_proposals()
+                        .get(&account_id)
+                        .map_or(0, |proposals| proposals.len())
+                        < MAX_DELEGATOR_VOTES,
+                "The delegator can't vote on more than {} proposals at the same time",
+                MAX_DELEGATOR_VOTES
+            );
+            let stake_shares = self.internal_get_account(&account_id).stake_shares;
+            assert!(
+                self.staked_amount_from_num_shares_rounded_down(stake_shares)
+                    >= MIN_DELEGATOR_VOTE_STAKE,
+                "The staked balance of the delegator is less than the minimum to vote"
+            );
+            let mut delegator_vote = DelegatorVote {
+                vote,
+                stake_shares,
+                storage_deposit: previous_vote.map_or(0, |v| v.storage_deposit),
+            };
+            self.internal_save_delegator_vote(
+                &voting_account_id,
+                proposal_id,
+                &account_id,
+                &delegator_vote,
+            );
+            // The new vote pays for the storage it takes, including the totals of the proposal.
+            let storage_usage = env::storage_usage();
+            if storage_usage > initial_storage_usage {
+                let storage_cost =
+                    Balance::from(storage_usage - initial_storage_usage) * STORAGE_PRICE_PER_BYTE;
+                assert!(
+                    refund >= storage_cost,
+                    "The attached deposit should cover the storage of the vote: {}",
+                    storage_cost
+                );
+                refund -= storage_cost;
+                self.last_total_balance += storage_cost;
+                delegator_vote.storage_deposit += storage_cost;
+                self.internal_save_delegator_vote(
+                    &voting_account_id,
+                    proposal_id,
+                    &account_id,
+                    &delegator_vote,
+                );
+            }
+        } else if let Some(previous_vote) = previous_vote {
+            refund += previous_vote.storage_deposit;
+            self.last_total_balance -= previous_vote.storage_deposit;
+        }
+        if refund > 0 {
+            Promise::new(account_id).transfer(refund);
+        }
+    }
+
+    /// Votes on the given proposal of the voting contract on behalf of the pool with the votes
+    /// of delegators. The stake of the pool is split pro rata to the staked balance of delegators
+    /// who voted for and against. The rest of the stake follows the owner's vote on the transfers
+    /// proposal, and abstains otherwise. Can be called by anyone.
+    pub fn forward_delegator_votes(
+        &mut self,
+        voting_account_id: AccountId,
+        proposal_id: u64,
+    ) -> Promise {
+        let totals = self.get_delegator_vote_totals(voting_account_id.clone(), proposal_id);
+        let mut yes = totals.yes.0;
+        let no = totals.no.0;
+        let mut abstain = self.total_staked_balance.saturating_sub(yes + no);
+        if proposal_id == TRANSFERS_PROPOSAL_ID && owner_votes().contains(&voting_account_id) {
+            yes += abstain;
+            abstain = 0;
+        }
+        assert!(
+            yes > 0 || no > 0,
+            "No staked balance of the pool voted on the proposal"
+        );
+
+        ext_voting::vote_on_proposal(
+            proposal_id,
+            ProposalVote::Split {
+                yes: yes.into(),
+                no: no.into(),
+                abstain: abstain.into(),
+            },
+            &voting_account_id,
+            NO_DEPOSIT,
+            VOTE_GAS,
+        )
+    }
+
     /****************/
     /* View methods */
     /****************/
@@ -365,6 +650,44 @@ impl StakingContract {
         self.paused
     }
 
+    /// Returns human-readable information about the pool set by the owner.
+    pub fn get_pool_metadata(&self) -> PoolMetadata {
+        self.internal_get_pool_metadata()
+    }
+
+    /// Returns the preference of the given delegator on the given proposal of the voting contract.
+    pub fn get_delegator_vote(
+        &self,
+        voting_account_id: AccountId,
+        proposal_id: u64,
+        account_id: AccountId,
+    ) -> Option<bool> {
+        delegator_votes()
+            .get(&(voting_account_id, proposal_id, account_id))
+            .map(|delegator_vote| delegator_vote.vote)
+    }
+
+    /// Returns the staked balance of delegators who voted for and against the given proposal of
+    /// the voting contract and the number of votes.
+    pub fn get_delegator_vote_totals(
+        &self,
+        voting_account_id: AccountId,
+        proposal_id: u64,
+    ) -> HumanReadableDelegatorVoteTotals {
+        let totals = delegator_vote_totals()
+            .get(&(voting_account_id, proposal_id))
+            .unwrap_or_default();
+        HumanReadableDelegatorVoteTotals {
+            yes: self
+                .staked_amount_from_num_shares_rounded_down(totals.yes_stake_shares)
+                .into(),
+            no: self
+                .staked_amount_from_num_shares_rounded_down(totals.no_stake_shares)
+                .into(),
+            num_votes: totals.num_votes,
+        }
+    }
+
     /// Returns human readable representation of the account for the given account ID.
     pub fn get_account(&self, account_id: AccountId) -> HumanReadableAccount {
         let account = self.internal_get_account(&account_id);
@@ -420,6 +743,51 @@ impl StakingContract {
         }
     }
 
+    /// Deploys the code returned by the factory and calls `migrate` on the new code.
+    /// The code must match the requested `code_hash` (base58 encoded sha256 of the code).
+    /// If `migrate` fails, the deployment is reverted as well.
+    pub fn on_get_code(&mut self, code_hash: String) -> Promise {
+        assert_eq!(
+            env::current_account_id(),
+            env::predecessor_account_id(),
+            "Can be called only as a callback"
+        );
+
+        assert_eq!(
+            env::promise_results_count(),
+            1,
+            "Contract expected a result on the callback"
+        );
+        let code = match env::promise_result(0) {
+            PromiseResult::Successful(code) => code,
+            _ => env::panic(
+                format!("Failed to get the code {} from the factory", code_hash).as_bytes(),
+            ),
+        };
+        assert_eq!(
+            bs58::encode(env::sha256(&code)).into_string(),
+            code_hash,
+            "The code returned by the factory doesn't match the requested hash"
+        );
+
+        env::log(format!("Upgrading the staking pool to the code {}", code_hash).as_bytes());
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(b"migrate".to_vec(), b"{}".to_vec(), NO_DEPOSIT, MIGRATE_GAS)
+    }
+
+    /// Migrates the state of the previous version of the contract after the upgrade.
+    /// Can only be called by the contract itself, see `upgrade`.
+    #[init]
+    pub fn migrate() -> Self {
+        assert_eq!(
+            env::current_account_id(),
+            env::predecessor_account_id(),
+            "Can be called only by the contract itself"
+        );
+        env::state_read().expect("The contract state is missing")
+    }
+
     /*******************/
     /* Owner's methods */
     /*******************/
@@ -447,16 +815,36 @@ impl StakingContract {
         }
     }
 
+    /// Owner's method.
+    /// Replaces human-readable information about the pool.
+    pub fn update_pool_metadata(&mut self, metadata: PoolMetadata) {
+        self.assert_owner();
+        self.internal_set_pool_metadata(&metadata);
+    }
+
     /// Owner's method.
     /// Calls `vote(is_vote)` on the given voting contract account ID on behalf of the pool.
+    /// Once delegators voted on the transfers proposal, the owner's vote only applies to the stake
+    /// of delegators who didn't vote and the votes are sent with `forward_delegator_votes`.
     pub fn vote(&mut self, voting_account_id: AccountId, is_vote: bool) -> Promise {
         self.assert_owner();
         assert!(
             env::is_valid_account_id(voting_account_id.as_bytes()),
             "Invalid voting account ID"
         );
+        if is_vote {
+            owner_votes().insert(&voting_account_id);
+        } else {
+            owner_votes().remove(&voting_account_id);
+        }
 
-        ext_voting::vote(is_vote, &voting_account_id, NO_DEPOSIT, VOTE_GAS)
+        let totals =
+            self.get_delegator_vote_totals(voting_account_id.clone(), TRANSFERS_PROPOSAL_ID);
+        if totals.yes.0 > 0 || totals.no.0 > 0 {
+            self.forward_delegator_votes(voting_account_id, TRANSFERS_PROPOSAL_ID)
+        } else {
+            ext_voting::vote(is_vote, &voting_account_id, NO_DEPOSIT, VOTE_GAS)
+        }
     }
 
     /// Owner's method.
@@ -480,6 +868,32 @@ impl StakingContract {
         self.paused = false;
         self.internal_restake();
     }
+
+    /// Owner's method.
+    /// Upgrades the staking pool to the code with the given hash approved by the factory that
+    /// created this pool (the parent account of the pool). The new code has to implement `migrate`.
+    pub fn upgrade(&mut self, code_hash: String) -> Promise {
+        self.assert_owner();
+
+        let account_id = env::current_account_id();
+        let factory_account_id = account_id
+            .splitn(2, '.')
+            .nth(1)
+            .expect("The staking pool is not created by a factory");
+
+        ext_factory::get_code(
+            code_hash.clone(),
+            &factory_account_id.to_string(),
+            NO_DEPOSIT,
+            GET_CODE_GAS,
+        )
+        .then(ext_self::on_get_code(
+            code_hash,
+            &account_id,
+            NO_DEPOSIT,
+            ON_GET_CODE_GAS,
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -524,6 +938,7 @@ mod tests {
                 owner,
                 Base58PublicKey::try_from(stake_public_key).unwrap(),
                 reward_fee_fraction,
+                None,
             );
             let last_total_staked_balance = contract.total_staked_balance;
             let last_total_stake_shares = contract.total_stake_shares;
@@ -613,6 +1028,421 @@ mod tests {
             .contains("\"actions\":[{\"Stake\":{\"stake\":0,"));
     }
 
+    #[test]
+    #[should_panic(expected = "The staking pool is not created by a factory")]
+    fn test_upgrade_without_factory() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(owner(), 0);
+        emulator.contract.upgrade("hash".to_string());
+    }
+
+    #[test]
+    fn test_on_get_code_deploys_and_migrates() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(staking(), 0);
+        testing_env_with_promise_results(
+            emulator.context.clone(),
+            PromiseResult::Successful(vec![1, 2, 3]),
+        );
+        let code_hash = bs58::encode(env::sha256(&[1, 2, 3])).into_string();
+        emulator.contract.on_get_code(code_hash);
+        let receipts = env::created_receipts();
+        assert_eq!(receipts.len(), 1);
+        let receipt = serde_json::to_string(&receipts[0]).unwrap();
+        assert!(receipt.contains("\"DeployContract\""));
+        assert!(receipt.contains("\"method_name\":\"migrate\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "The code returned by the factory doesn't match the requested hash")]
+    fn test_on_get_code_hash_mismatch() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(staking(), 0);
+        testing_env_with_promise_results(
+            emulator.context.clone(),
+            PromiseResult::Successful(vec![1, 2, 3]),
+        );
+        let code_hash = bs58::encode(env::sha256(&[4, 5, 6])).into_string();
+        emulator.contract.on_get_code(code_hash);
+    }
+
+    #[test]
+    fn test_pool_metadata() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(owner(), 0);
+        assert!(emulator.contract.get_pool_metadata().name.is_none());
+        emulator.contract.update_pool_metadata(PoolMetadata {
+            name: Some("Pool".to_string()),
+            website: Some("https://example.com".to_string()),
+            ..Default::default()
+        });
+        let metadata = emulator.contract.get_pool_metadata();
+        assert_eq!(metadata.name, Some("Pool".to_string()));
+        assert_eq!(metadata.website, Some("https://example.com".to_string()));
+        assert!(metadata.description.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "The pool metadata name can't be longer than 64 bytes")]
+    fn test_pool_metadata_too_long() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(owner(), 0);
+        emulator.contract.update_pool_metadata(PoolMetadata {
+            name: Some("a".repeat(65)),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only be called by the owner")]
+    fn test_pool_metadata_not_owner() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(bob(), 0);
+        emulator
+            .contract
+            .update_pool_metadata(PoolMetadata::default());
+    }
+
+    /// The deposit attached to delegator votes, more than enough to cover the storage.
+    const VOTE_DEPOSIT: Balance = 10_000_000_000_000_000_000_000;
+
+    #[test]
+    fn test_delegator_votes() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        for (account_id, amount) in vec![(alice(), ntoy(300)), (bob(), ntoy(100))] {
+            emulator.update_context(account_id.clone(), amount);
+            emulator.contract.deposit_and_stake();
+            emulator.amount += amount;
+            emulator.simulate_stake_call();
+        }
+        let voting = "voting".to_string();
+        emulator.update_context(alice(), VOTE_DEPOSIT);
+        emulator
+            .contract
+            .delegator_vote(voting.clone(), 1, Some(true));
+        emulator.amount += VOTE_DEPOSIT;
+        emulator.update_context(bob(), VOTE_DEPOSIT);
+        emulator
+            .contract
+            .delegator_vote(voting.clone(), 1, Some(false));
+        emulator.amount += VOTE_DEPOSIT;
+        // Voting again doesn't charge the storage deposit.
+        emulator.update_context(bob(), 0);
+        emulator
+            .contract
+            .delegator_vote(voting.clone(), 1, Some(false));
+        assert_eq!(
+            emulator
+                .contract
+                .get_delegator_vote(voting.clone(), 1, bob()),
+            Some(false)
+        );
+        let totals = emulator
+            .contract
+            .get_delegator_vote_totals(voting.clone(), 1);
+        assert_eq!(totals.yes.0, ntoy(300));
+        assert_eq!(totals.no.0, ntoy(100));
+        assert_eq!(totals.num_votes, 2);
+        assert_eq!(
+            emulator
+                .contract
+                .get_delegator_vote_totals(voting.clone(), 0)
+                .num_votes,
+            0
+        );
+
+        emulator.update_context(bob(), 0);
+        emulator.contract.forward_delegator_votes(voting.clone(), 1);
+        let receipts = env::created_receipts();
+        assert_eq!(receipts.len(), 1);
+        let receipt = serde_json::to_string(&receipts[0]).unwrap();
+        assert!(receipt.contains("\"method_name\":\"vote_on_proposal\""));
+
+        let last_total_balance = emulator.contract.last_total_balance;
+        emulator.update_context(bob(), 0);
+        emulator.contract.delegator_vote(voting.clone(), 1, None);
+        assert_eq!(
+            emulator
+                .contract
+                .get_delegator_vote(voting.clone(), 1, bob()),
+            None
+        );
+        assert_eq!(
+            emulator
+                .contract
+                .get_delegator_vote_totals(voting, 1)
+                .num_votes,
+            1
+        );
+        // The storage deposit is refunded.
+        assert!(emulator.contract.last_total_balance < last_total_balance);
+        let receipts = env::created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert!(serde_json::to_string(&receipts[0])
+            .unwrap()
+            .contains("\"Transfer\""));
+    }
+
+    /// Returns the JSON arguments of the function call of the only created receipt.
+    fn get_forwarded_vote() -> serde_json::Value {
+        let receipts = env::created_receipts();
+        assert_eq!(receipts.len(), 1);
+        let receipt: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&receipts[0]).unwrap()).unwrap();
+        serde_json::from_str(
+            receipt["actions"][0]["FunctionCall"]["args"]
+                .as_str()
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unstake_lowers_delegator_votes() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(bob(), ntoy(100));
+        emulator.contract.deposit_and_stake();
+        emulator.amount += ntoy(100);
+        emulator.simulate_stake_call();
+        let voting = "voting".to_string();
+        for proposal_id in 1..3 {
+            emulator.update_context(bob(), VOTE_DEPOSIT);
+            emulator
+                .contract
+                .delegator_vote(voting.clone(), proposal_id, Some(true));
+            emulator.amount += VOTE_DEPOSIT;
+        }
+
+        emulator.update_context(bob(), 0);
+        emulator.contract.unstake(ntoy(60).into());
+        emulator.simulate_stake_call();
+        for proposal_id in 1..3 {
+            assert_eq!(
+                emulator
+                    .contract
+                    .get_delegator_vote_totals(voting.clone(), proposal_id)
+                    .yes
+                    .0,
+                ntoy(40)
+            );
+        }
+
+        // The withdrawn stake can't be counted again by another account.
+        emulator.update_context(bob(), 0);
+        emulator.contract.unstake_all();
+        emulator.simulate_stake_call();
+        emulator.skip_epochs(4);
+        emulator.update_context(bob(), 0);
+        emulator.contract.withdraw_all();
+        emulator.amount -= ntoy(100);
+        emulator.update_context(alice(), ntoy(100));
+        emulator.contract.deposit_and_stake();
+        emulator.amount += ntoy(100);
+        emulator.simulate_stake_call();
+        emulator.update_context(alice(), VOTE_DEPOSIT);
+        emulator
+            .contract
+            .delegator_vote(voting.clone(), 1, Some(true));
+        let totals = emulator.contract.get_delegator_vote_totals(voting, 1);
+        assert_eq!(
+            totals.yes.0,
+            emulator.contract.get_account_staked_balance(alice()).0
+        );
+        assert_eq!(totals.num_votes, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "The delegator can't vote on more than 16 proposals")]
+    fn test_delegator_vote_too_many_proposals() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(bob(), ntoy(100));
+        emulator.contract.deposit_and_stake();
+        emulator.amount += ntoy(100);
+        emulator.simulate_stake_call();
+        for proposal_id in 0..=MAX_DELEGATOR_VOTES as u64 {
+            emulator.update_context(bob(), VOTE_DEPOSIT);
+            emulator
+                .contract
+                .delegator_vote("voting".to_string(), proposal_id, Some(true));
+            emulator.amount += VOTE_DEPOSIT;
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The staked balance of the delegator is less than the minimum")]
+    fn test_delegator_vote_without_stake() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(bob(), VOTE_DEPOSIT);
+        emulator
+            .contract
+            .delegator_vote("voting".to_string(), 1, Some(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "The staked balance of the delegator is less than the minimum")]
+    fn test_delegator_vote_below_min_stake() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(bob(), ntoy(1) / 2);
+        emulator.contract.deposit_and_stake();
+        emulator.amount += ntoy(1) / 2;
+        emulator.simulate_stake_call();
+        emulator.update_context(bob(), VOTE_DEPOSIT);
+        emulator
+            .contract
+            .delegator_vote("voting".to_string(), 1, Some(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "The attached deposit should cover the storage of the vote")]
+    fn test_delegator_vote_without_deposit() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(bob(), ntoy(100));
+        emulator.contract.deposit_and_stake();
+        emulator.amount += ntoy(100);
+        emulator.simulate_stake_call();
+        emulator.update_context(bob(), 0);
+        emulator
+            .contract
+            .delegator_vote("voting".to_string(), 1, Some(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid voting account ID")]
+    fn test_delegator_vote_invalid_voting_account() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(bob(), VOTE_DEPOSIT);
+        emulator
+            .contract
+            .delegator_vote("Invalid Voting".to_string(), 1, Some(true));
+    }
+
+    #[test]
+    fn test_owner_vote_after_delegator_votes() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(alice(), ntoy(300));
+        emulator.contract.deposit_and_stake();
+        emulator.amount += ntoy(300);
+        emulator.simulate_stake_call();
+        emulator.update_context(bob(), ntoy(1));
+        emulator.contract.deposit_and_stake();
+        emulator.amount += ntoy(1);
+        emulator.simulate_stake_call();
+        let voting = "voting".to_string();
+        emulator.update_context(bob(), VOTE_DEPOSIT);
+        emulator
+            .contract
+            .delegator_vote(voting.clone(), TRANSFERS_PROPOSAL_ID, Some(false));
+        emulator.amount += VOTE_DEPOSIT;
+        let total_staked_balance = emulator.contract.total_staked_balance;
+
+        // A small delegator doesn't steer the stake of others.
+        emulator.update_context(alice(), 0);
+        emulator
+            .contract
+            .forward_delegator_votes(voting.clone(), TRANSFERS_PROPOSAL_ID);
+        let vote = get_forwarded_vote();
+        assert_eq!(vote["vote"]["Split"]["yes"], "0");
+        assert_eq!(vote["vote"]["Split"]["no"], ntoy(1).to_string());
+        assert_eq!(
+            vote["vote"]["Split"]["abstain"],
+            (total_staked_balance - ntoy(1)).to_string()
+        );
+
+        // The owner's vote applies to the stake that didn't vote.
+        emulator.update_context(owner(), 0);
+        emulator.contract.vote(voting.clone(), true);
+        let vote = get_forwarded_vote();
+        assert_eq!(
+            vote["vote"]["Split"]["yes"],
+            (total_staked_balance - ntoy(1)).to_string()
+        );
+        assert_eq!(vote["vote"]["Split"]["no"], ntoy(1).to_string());
+        assert_eq!(vote["vote"]["Split"]["abstain"], "0");
+
+        // Other proposals don't follow the owner's vote.
+        emulator.update_context(bob(), VOTE_DEPOSIT);
+        emulator
+            .contract
+            .delegator_vote(voting.clone(), 1, Some(true));
+        emulator.amount += VOTE_DEPOSIT;
+        emulator.update_context(alice(), 0);
+        emulator.contract.forward_delegator_votes(voting, 1);
+        let vote = get_forwarded_vote();
+        assert_eq!(vote["vote"]["Split"]["yes"], ntoy(1).to_string());
+        assert_eq!(vote["vote"]["Split"]["no"], "0");
+    }
+
+    #[test]
+    fn test_owner_vote_without_delegator_votes() {
+        let mut emulator = Emulator::new(
+            owner(),
+            "KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".to_string(),
+            zero_fee(),
+        );
+        emulator.update_context(owner(), 0);
+        emulator.contract.vote("voting".to_string(), true);
+        let receipts = env::created_receipts();
+        assert_eq!(receipts.len(), 1);
+        let receipt = serde_json::to_string(&receipts[0]).unwrap();
+        assert!(receipt.contains("\"method_name\":\"vote\""));
+    }
+
     #[test]
     fn test_deposit_withdraw() {
         let mut emulator = Emulator::new(
@@ -0,0 +1,139 @@
+//! Layouts of `near_sdk::collections`. Only the headers of the collections are stored in the
+//! parent struct, the elements are stored under their own keys.
+
+use crate::{read, read_expected, Entry, RawState, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Layout of `near_sdk::collections::Vector`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct Vector {
+    pub len: u64,
+    pub prefix: Vec<u8>,
+}
+
+impl Vector {
+    pub fn new(prefix: Vec<u8>) -> Self {
+        Self { len: 0, prefix }
+    }
+
+    /// The key of the element at the given index.
+    pub fn element_key(&self, index: u64) -> Vec<u8> {
+        [self.prefix.as_slice(), &index.to_le_bytes()].concat()
+    }
+}
+
+/// Layout of `near_sdk::collections::UnorderedMap`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct UnorderedMap {
+    /// Prefix of the map from the key to the index in `keys` and `values`.
+    pub key_index_prefix: Vec<u8>,
+    pub keys: Vector,
+    pub values: Vector,
+}
+
+impl UnorderedMap {
+    pub fn new(prefix: &[u8]) -> Self {
+        Self {
+            key_index_prefix: [prefix, b"i"].concat(),
+            keys: Vector::new([prefix, b"k"].concat()),
+            values: Vector::new([prefix, b"v"].concat()),
+        }
+    }
+
+    /// The key of the index of the given map key.
+    pub fn index_key<K: BorshSerialize>(&self, key: &K) -> Vec<u8> {
+        [self.key_index_prefix.as_slice(), &key.try_to_vec().unwrap()].concat()
+    }
+
+    /// Returns the value of the given key.
+    pub fn get<K: BorshSerialize, V: BorshDeserialize>(
+        &self,
+        state: &RawState,
+        key: &K,
+    ) -> Result<Option<V>> {
+        match read::<u64>(state, &self.index_key(key))? {
+            Some(index) => read_expected(state, &self.values.element_key(index)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the entries to insert or replace the value of the given key.
+    /// Inserting a new key updates the lengths of this map, so the parent struct has to be saved
+    /// as well.
+    pub fn insert<K: BorshSerialize, V: BorshSerialize>(
+        &mut self,
+        state: &RawState,
+        key: &K,
+        value: &V,
+    ) -> Result<Vec<Entry>> {
+        if let Some(index) = read::<u64>(state, &self.index_key(key))? {
+            return Ok(vec![Entry::new(self.values.element_key(index), value)]);
+        }
+        let index = self.keys.len;
+        self.keys.len += 1;
+        self.values.len += 1;
+        Ok(vec![
+            Entry::new(self.index_key(key), &index),
+            Entry::new(self.keys.element_key(index), key),
+            Entry::new(self.values.element_key(index), value),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply;
+
+    #[test]
+    fn test_unordered_map_keys() {
+        let map = UnorderedMap::new(b"u");
+        assert_eq!(
+            map.index_key(&"alice".to_string()),
+            b"ui\x05\x00\x00\x00alice".to_vec()
+        );
+        assert_eq!(
+            map.keys.element_key(1),
+            b"uk\x01\x00\x00\x00\x00\x00\x00\x00".to_vec()
+        );
+        assert_eq!(
+            map.values.element_key(1),
+            b"uv\x01\x00\x00\x00\x00\x00\x00\x00".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_unordered_map_insert_get() {
+        let mut state = RawState::new();
+        let mut map = UnorderedMap::new(b"u");
+
+        let entries = map.insert(&state, &"alice".to_string(), &10u128).unwrap();
+        assert_eq!(entries.len(), 3);
+        apply(&mut state, &entries);
+        let entries = map.insert(&state, &"bob".to_string(), &20u128).unwrap();
+        apply(&mut state, &entries);
+        assert_eq!(map.keys.len, 2);
+
+        // Replacing an existing value only writes the value.
+        let entries = map.insert(&state, &"alice".to_string(), &30u128).unwrap();
+        assert_eq!(
+            entries,
+            vec![Entry::new(map.values.element_key(0), &30u128)]
+        );
+        apply(&mut state, &entries);
+        assert_eq!(map.keys.len, 2);
+
+        assert_eq!(
+            map.get::<_, u128>(&state, &"alice".to_string()).unwrap(),
+            Some(30)
+        );
+        assert_eq!(
+            map.get::<_, u128>(&state, &"bob".to_string()).unwrap(),
+            Some(20)
+        );
+        assert_eq!(
+            map.get::<_, u128>(&state, &"carol".to_string()).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Typed patches of the core contracts state for the state-manipulation contract.
+//!
+//! The state of the contract is read from the raw key/value pairs of the account, e.g. from
+//! `view_state` RPC query. Patches are produced as [`Entry`] writes, which are converted to the
+//! input of `replace` or `replace_checked` methods.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub mod collections;
+pub mod lockup;
+pub mod staking_pool;
+
+/// Raw key/value pairs of the contract account.
+pub type RawState = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// The key of the contract struct for contracts built with `near_bindgen`.
+pub const STATE_KEY: &[u8] = b"STATE";
+
+#[derive(Debug)]
+pub enum Error {
+    /// The key is expected to be in the state.
+    MissingKey(Vec<u8>),
+    /// The value doesn't match the expected layout.
+    Borsh(std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingKey(key) => write!(f, "Missing key {}", base64::encode(key)),
+            Error::Borsh(err) => write!(f, "Failed to deserialize value: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Borsh(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A single write to the contract storage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+impl Entry {
+    /// Creates the entry with Borsh serialized `value`.
+    pub fn new<T: BorshSerialize>(key: Vec<u8>, value: &T) -> Self {
+        Self {
+            key,
+            value: value.try_to_vec().unwrap(),
+        }
+    }
+}
+
+/// Reads and deserializes the value at the given key.
+pub(crate) fn read<T: BorshDeserialize>(state: &RawState, key: &[u8]) -> Result<Option<T>> {
+    state
+        .get(key)
+        .map(|value| T::try_from_slice(value))
+        .transpose()
+        .map_err(Error::from)
+}
+
+/// Reads and deserializes the value at the given key, which should be present.
+pub(crate) fn read_expected<T: BorshDeserialize>(state: &RawState, key: &[u8]) -> Result<T> {
+    read(state, key)?.ok_or_else(|| Error::MissingKey(key.to_vec()))
+}
+
+/// Applies the entries to the state, the same way `replace` does.
+pub fn apply(state: &mut RawState, entries: &[Entry]) {
+    for entry in entries {
+        state.insert(entry.key.clone(), entry.value.clone());
+    }
+}
+
+/// Returns the JSON input of `replace` method to write the entries.
+pub fn replace_input(entries: &[Entry]) -> serde_json::Value {
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|entry| (base64::encode(&entry.key), base64::encode(&entry.value)))
+        .collect();
+    json!({ "entries": entries })
+}
+
+/// Returns the JSON input of `replace_checked` method to write the entries. Every entry expects
+/// the value it has in the given `state`, so the patch fails if the state changed since it was
+/// read.
+pub fn replace_checked_input(state: &RawState, entries: &[Entry]) -> serde_json::Value {
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            let mut input = json!({
+                "key": base64::encode(&entry.key),
+                "value": base64::encode(&entry.value),
+            });
+            match state.get(&entry.key) {
+                Some(value) => input["expected_value"] = json!(base64::encode(value)),
+                None => input["expected_absent"] = json!(true),
+            }
+            input
+        })
+        .collect();
+    json!({ "entries": entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_input() {
+        let entries = vec![Entry::new(STATE_KEY.to_vec(), &1u8)];
+        assert_eq!(
+            replace_input(&entries),
+            json!({ "entries": [["U1RBVEU=", "AQ=="]] })
+        );
+    }
+
+    #[test]
+    fn test_replace_checked_input() {
+        let mut state = RawState::new();
+        state.insert(STATE_KEY.to_vec(), vec![0]);
+        let entries = vec![
+            Entry::new(STATE_KEY.to_vec(), &1u8),
+            Entry::new(b"a".to_vec(), &1u8),
+        ];
+        assert_eq!(
+            replace_checked_input(&state, &entries),
+            json!({ "entries": [
+                { "key": "U1RBVEU=", "value": "AQ==", "expected_value": "AA==" },
+                { "key": "YQ==", "value": "AQ==", "expected_absent": true },
+            ]})
+        );
+    }
+}
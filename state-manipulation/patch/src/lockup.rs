@@ -0,0 +1,209 @@
+//! Layout of the lockup contract state.
+//!
+//! `LockupContract` is stored at the `STATE` key and doesn't use collections. JSON wrappers of
+//! the contract (`U64`, `U128` and `Base64VecU8`) have the same Borsh layout as the inner types.
+
+use crate::{read_expected, Entry, RawState, Result, STATE_KEY};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Layout of `lockup_contract::TransfersInformation`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub enum TransfersInformation {
+    TransfersEnabled { transfers_timestamp: u64 },
+    TransfersDisabled { transfer_poll_account_id: String },
+}
+
+/// Layout of `lockup_contract::LockupInformation`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct LockupInformation {
+    pub lockup_amount: u128,
+    pub termination_withdrawn_tokens: u128,
+    pub lockup_duration: u64,
+    pub release_duration: Option<u64>,
+    pub lockup_timestamp: Option<u64>,
+    pub transfers_information: TransfersInformation,
+}
+
+/// Layout of `lockup_contract::TransactionStatus`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq)]
+pub enum TransactionStatus {
+    Idle,
+    Busy,
+}
+
+/// Layout of `lockup_contract::StakingInformation`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct StakingInformation {
+    pub staking_pool_account_id: String,
+    pub status: TransactionStatus,
+    pub deposit_amount: u128,
+}
+
+/// Layout of `lockup_contract::VestingSchedule`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct VestingSchedule {
+    pub start_timestamp: u64,
+    pub cliff_timestamp: u64,
+    pub end_timestamp: u64,
+}
+
+/// Layout of `lockup_contract::TerminationStatus`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq)]
+pub enum TerminationStatus {
+    VestingTerminatedWithDeficit,
+    UnstakingInProgress,
+    EverythingUnstaked,
+    WithdrawingFromStakingPoolInProgress,
+    ReadyToWithdraw,
+    WithdrawingFromAccountInProgress,
+}
+
+/// Layout of `lockup_contract::TerminationInformation`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct TerminationInformation {
+    pub unvested_amount: u128,
+    pub status: TerminationStatus,
+}
+
+/// Layout of `lockup_contract::VestingInformation`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub enum VestingInformation {
+    None,
+    VestingHash(Vec<u8>),
+    VestingSchedule(VestingSchedule),
+    Terminating(TerminationInformation),
+}
+
+/// Layout of `lockup_contract::LockupContract`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct LockupContract {
+    pub owner_account_id: String,
+    pub lockup_information: LockupInformation,
+    pub vesting_information: VestingInformation,
+    pub staking_pool_whitelist_account_id: String,
+    pub staking_information: Option<StakingInformation>,
+    pub foundation_account_id: Option<String>,
+}
+
+impl LockupContract {
+    /// Reads the contract from the state.
+    pub fn read(state: &RawState) -> Result<Self> {
+        read_expected(state, STATE_KEY)
+    }
+
+    /// Returns the entry to save the contract.
+    pub fn entry(&self) -> Entry {
+        Entry::new(STATE_KEY.to_vec(), self)
+    }
+
+    /// Returns the entry to set the status of the transaction with the staking pool, e.g. to
+    /// unlock the contract stuck in `Busy` status. `None` if the staking pool is not selected.
+    pub fn set_staking_status(&mut self, status: TransactionStatus) -> Option<Entry> {
+        self.staking_information.as_mut()?.status = status;
+        Some(self.entry())
+    }
+
+    /// Returns the entry to set the status of the vesting termination. `None` if the vesting is
+    /// not being terminated.
+    pub fn set_termination_status(&mut self, status: TerminationStatus) -> Option<Entry> {
+        match &mut self.vesting_information {
+            VestingInformation::Terminating(termination_information) => {
+                termination_information.status = status;
+                Some(self.entry())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply;
+
+    fn contract() -> LockupContract {
+        LockupContract {
+            owner_account_id: "owner".to_string(),
+            lockup_information: LockupInformation {
+                lockup_amount: 1000,
+                termination_withdrawn_tokens: 0,
+                lockup_duration: 0,
+                release_duration: Some(100),
+                lockup_timestamp: None,
+                transfers_information: TransfersInformation::TransfersEnabled {
+                    transfers_timestamp: 10,
+                },
+            },
+            vesting_information: VestingInformation::Terminating(TerminationInformation {
+                unvested_amount: 500,
+                status: TerminationStatus::UnstakingInProgress,
+            }),
+            staking_pool_whitelist_account_id: "whitelist".to_string(),
+            staking_information: Some(StakingInformation {
+                staking_pool_account_id: "pool".to_string(),
+                status: TransactionStatus::Busy,
+                deposit_amount: 100,
+            }),
+            foundation_account_id: Some("foundation".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_contract_round_trip() {
+        let mut state = RawState::new();
+        let contract = contract();
+        apply(&mut state, &[contract.entry()]);
+        assert_eq!(LockupContract::read(&state).unwrap(), contract);
+    }
+
+    #[test]
+    fn test_staking_information_layout() {
+        let staking_information = contract().staking_information.unwrap();
+        let expected: Vec<u8> = [
+            &b"\x04\x00\x00\x00pool"[..],
+            // `TransactionStatus::Busy`
+            &[1],
+            &100u128.to_le_bytes(),
+        ]
+        .concat();
+        assert_eq!(staking_information.try_to_vec().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_set_status_round_trip() {
+        let mut state = RawState::new();
+        let mut contract = contract();
+        apply(&mut state, &[contract.entry()]);
+
+        let entry = contract
+            .set_staking_status(TransactionStatus::Idle)
+            .unwrap();
+        apply(&mut state, &[entry]);
+        let entry = contract
+            .set_termination_status(TerminationStatus::EverythingUnstaked)
+            .unwrap();
+        apply(&mut state, &[entry]);
+
+        let mut contract = LockupContract::read(&state).unwrap();
+        assert_eq!(
+            contract.staking_information.as_ref().unwrap().status,
+            TransactionStatus::Idle
+        );
+        assert_eq!(
+            contract.vesting_information,
+            VestingInformation::Terminating(TerminationInformation {
+                unvested_amount: 500,
+                status: TerminationStatus::EverythingUnstaked,
+            })
+        );
+
+        contract.staking_information = None;
+        contract.vesting_information = VestingInformation::None;
+        assert!(contract
+            .set_staking_status(TransactionStatus::Idle)
+            .is_none());
+        assert!(contract
+            .set_termination_status(TerminationStatus::ReadyToWithdraw)
+            .is_none());
+    }
+}
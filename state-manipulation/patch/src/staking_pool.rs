@@ -0,0 +1,199 @@
+//! Layout of the staking pool contract state.
+//!
+//! `StakingContract` is stored at the `STATE` key and the accounts are stored in the `u`-prefixed
+//! `accounts` map. The pool metadata (`m` key), the delegator votes (`v` prefix), their totals
+//! (`t` prefix), the proposals each delegator voted on (`p` prefix) and the owner votes (`o` prefix)
+//! are not covered.
+
+use crate::collections::UnorderedMap;
+use crate::{read_expected, Entry, RawState, Result, STATE_KEY};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Prefix of the `accounts` map.
+pub const ACCOUNTS_PREFIX: &[u8] = b"u";
+
+/// Layout of `staking_pool::Account`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Default, PartialEq)]
+pub struct Account {
+    pub unstaked: u128,
+    pub stake_shares: u128,
+    pub unstaked_available_epoch_height: u64,
+}
+
+/// Layout of `staking_pool::RewardFeeFraction`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct RewardFeeFraction {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+/// Layout of `staking_pool::StakingContract`.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct StakingContract {
+    pub owner_id: String,
+    pub stake_public_key: Vec<u8>,
+    pub last_epoch_height: u64,
+    pub last_total_balance: u128,
+    pub total_stake_shares: u128,
+    pub total_staked_balance: u128,
+    pub reward_fee_fraction: RewardFeeFraction,
+    pub accounts: UnorderedMap,
+    pub paused: bool,
+}
+
+impl StakingContract {
+    /// Reads the contract from the state.
+    pub fn read(state: &RawState) -> Result<Self> {
+        read_expected(state, STATE_KEY)
+    }
+
+    /// Returns the entry to save the contract.
+    pub fn entry(&self) -> Entry {
+        Entry::new(STATE_KEY.to_vec(), self)
+    }
+
+    pub fn get_account(&self, state: &RawState, account_id: &str) -> Result<Option<Account>> {
+        self.accounts.get(state, &account_id.to_string())
+    }
+
+    /// Returns the entries to save the account. If the account is new, the contract is saved as
+    /// well.
+    pub fn set_account(
+        &mut self,
+        state: &RawState,
+        account_id: &str,
+        account: &Account,
+    ) -> Result<Vec<Entry>> {
+        let num_accounts = self.accounts.keys.len;
+        let mut entries = self
+            .accounts
+            .insert(state, &account_id.to_string(), account)?;
+        if self.accounts.keys.len != num_accounts {
+            entries.push(self.entry());
+        }
+        Ok(entries)
+    }
+
+    /// Returns the entries to set the unstaked balance of the account.
+    pub fn set_account_unstaked(
+        &mut self,
+        state: &RawState,
+        account_id: &str,
+        unstaked: u128,
+    ) -> Result<Vec<Entry>> {
+        let mut account = self.get_account(state, account_id)?.unwrap_or_default();
+        account.unstaked = unstaked;
+        self.set_account(state, account_id, &account)
+    }
+
+    /// Returns the entries to set the stake shares of the account.
+    /// NOTE: The total stake shares of the contract are not updated.
+    pub fn set_account_stake_shares(
+        &mut self,
+        state: &RawState,
+        account_id: &str,
+        stake_shares: u128,
+    ) -> Result<Vec<Entry>> {
+        let mut account = self.get_account(state, account_id)?.unwrap_or_default();
+        account.stake_shares = stake_shares;
+        self.set_account(state, account_id, &account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{apply, Error};
+
+    fn contract() -> StakingContract {
+        StakingContract {
+            owner_id: "owner".to_string(),
+            stake_public_key: vec![0; 33],
+            last_epoch_height: 10,
+            last_total_balance: 100,
+            total_stake_shares: 50,
+            total_staked_balance: 50,
+            reward_fee_fraction: RewardFeeFraction {
+                numerator: 10,
+                denominator: 100,
+            },
+            accounts: UnorderedMap::new(ACCOUNTS_PREFIX),
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_contract_round_trip() {
+        let mut state = RawState::new();
+        assert!(matches!(
+            StakingContract::read(&state),
+            Err(Error::MissingKey(_))
+        ));
+
+        let contract = contract();
+        apply(&mut state, &[contract.entry()]);
+        assert_eq!(StakingContract::read(&state).unwrap(), contract);
+    }
+
+    #[test]
+    fn test_contract_layout() {
+        let mut state = RawState::new();
+        apply(&mut state, &[contract().entry()]);
+        let value = state.get(STATE_KEY).unwrap();
+        // owner_id: "owner"
+        assert_eq!(&value[..9], b"\x05\x00\x00\x00owner");
+        // accounts map header followed by `paused`.
+        let accounts_and_paused: Vec<u8> = [
+            &b"\x02\x00\x00\x00ui"[..],
+            &0u64.to_le_bytes(),
+            b"\x02\x00\x00\x00uk",
+            &0u64.to_le_bytes(),
+            b"\x02\x00\x00\x00uv",
+            &[0],
+        ]
+        .concat();
+        assert_eq!(&value[value.len() - 35..], accounts_and_paused.as_slice());
+    }
+
+    #[test]
+    fn test_set_account_round_trip() {
+        let mut state = RawState::new();
+        let mut contract = contract();
+        apply(&mut state, &[contract.entry()]);
+
+        // A new account also updates the contract.
+        let entries = contract.set_account_unstaked(&state, "alice", 100).unwrap();
+        assert_eq!(entries.len(), 4);
+        apply(&mut state, &entries);
+
+        let mut contract = StakingContract::read(&state).unwrap();
+        assert_eq!(contract.accounts.keys.len, 1);
+        assert_eq!(
+            contract.get_account(&state, "alice").unwrap(),
+            Some(Account {
+                unstaked: 100,
+                ..Default::default()
+            })
+        );
+
+        // An existing account only writes the account.
+        let entries = contract
+            .set_account_stake_shares(&state, "alice", 20)
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        apply(&mut state, &entries);
+        let entries = contract.set_account_unstaked(&state, "alice", 30).unwrap();
+        apply(&mut state, &entries);
+
+        assert_eq!(
+            contract.get_account(&state, "alice").unwrap(),
+            Some(Account {
+                unstaked: 30,
+                stake_shares: 20,
+                unstaked_available_epoch_height: 0,
+            })
+        );
+        assert_eq!(contract.get_account(&state, "bob").unwrap(), None);
+        assert_eq!(StakingContract::read(&state).unwrap(), contract);
+    }
+}
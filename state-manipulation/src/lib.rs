@@ -9,8 +9,12 @@ use alloc::vec::Vec;
 const ATOMIC_OP_REGISTER: u64 = 0;
 const EVICTED_REGISTER: u64 = 8;
 
-#[cfg(all(not(feature = "clean"), not(feature = "replace")))]
-core::compile_error!("one of the `clean` or `replace` features must be set");
+#[cfg(all(
+    not(feature = "clean"),
+    not(feature = "replace"),
+    not(feature = "dump")
+))]
+core::compile_error!("one of the `clean`, `replace` or `dump` features must be set");
 
 // Set up global allocator by default if in wasm32 architecture.
 #[cfg(target_arch = "wasm32")]
@@ -40,10 +44,22 @@ fn register_len(register_id: u64) -> Option<u64> {
     }
 }
 
+/// Reads the content of the register, `None` if the register is not used.
+fn read_register(register_id: u64) -> Option<Vec<u8>> {
+    let len = register_len(register_id)?;
+
+    let buffer = vec![0u8; len as usize];
+
+    // Read data from register into buffer
+    unsafe { near_sys::read_register(register_id, buffer.as_ptr() as _) };
+
+    Some(buffer)
+}
+
 #[cfg(feature = "replace")]
-/// Writes key-value into storage.
-fn storage_write(key: &[u8], value: &[u8]) {
-    unsafe {
+/// Writes key-value into storage. Returns the evicted value, if any.
+fn storage_write(key: &[u8], value: &[u8]) -> Option<Vec<u8>> {
+    let evicted = unsafe {
         near_sys::storage_write(
             key.len() as _,
             key.as_ptr() as _,
@@ -52,6 +68,29 @@ fn storage_write(key: &[u8], value: &[u8]) {
             EVICTED_REGISTER,
         )
     };
+    if evicted == 1 {
+        read_register(EVICTED_REGISTER)
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "replace")]
+/// Aborts the call with the given message, reverting all state changes.
+fn panic_str(message: &str) -> ! {
+    unsafe { near_sys::panic_utf8(message.len() as _, message.as_ptr() as _) };
+}
+
+#[cfg(feature = "dump")]
+/// Reads the value at given key.
+fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
+    let exists =
+        unsafe { near_sys::storage_read(key.len() as _, key.as_ptr() as _, ATOMIC_OP_REGISTER) };
+    if exists == 1 {
+        read_register(ATOMIC_OP_REGISTER)
+    } else {
+        None
+    }
 }
 
 #[cfg(feature = "clean")]
@@ -60,16 +99,20 @@ fn storage_remove(key: &[u8]) {
     unsafe { near_sys::storage_remove(key.len() as _, key.as_ptr() as _, EVICTED_REGISTER) };
 }
 
-fn input() -> Option<Vec<u8>> {
-    unsafe { near_sys::input(ATOMIC_OP_REGISTER) };
-    let len = register_len(ATOMIC_OP_REGISTER)?;
-
-    let buffer = vec![0u8; len as usize];
+#[cfg(feature = "clean")]
+/// Gas left for the rest of the call, excluding the gas burnt so far.
+fn remaining_gas() -> u64 {
+    unsafe { near_sys::prepaid_gas() - near_sys::used_gas() }
+}
 
-    // Read data from register into buffer
-    unsafe { near_sys::read_register(ATOMIC_OP_REGISTER, buffer.as_ptr() as _) };
+/// Returns the given bytes as the result of the call.
+fn value_return(value: &[u8]) {
+    unsafe { near_sys::value_return(value.len() as _, value.as_ptr() as _) };
+}
 
-    Some(buffer)
+fn input() -> Option<Vec<u8>> {
+    unsafe { near_sys::input(ATOMIC_OP_REGISTER) };
+    read_register(ATOMIC_OP_REGISTER)
 }
 
 #[cfg(feature = "replace")]
@@ -91,6 +134,67 @@ pub fn replace() {
     }
 }
 
+#[cfg(feature = "replace")]
+#[no_mangle]
+pub fn replace_checked() {
+    #[derive(serde::Deserialize)]
+    struct ReplaceCheckedEntry<'a> {
+        key: &'a str,
+        value: &'a str,
+        /// Value the key is expected to have before the write.
+        #[serde(default)]
+        expected_value: Option<&'a str>,
+        /// Whether the key is expected to be absent before the write.
+        #[serde(default)]
+        expected_absent: bool,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct ReplaceCheckedInput<'a> {
+        #[serde(borrow)]
+        entries: Vec<ReplaceCheckedEntry<'a>>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Overwritten<'a> {
+        key: &'a str,
+        /// Previous value in base64, `None` if the key was absent.
+        previous_value: Option<alloc::string::String>,
+    }
+
+    let input = input().unwrap();
+    let args: ReplaceCheckedInput = serde_json::from_slice(&input).unwrap();
+    let mut overwritten = Vec::with_capacity(args.entries.len());
+    for entry in args.entries {
+        if entry.expected_absent && entry.expected_value.is_some() {
+            panic_str(&format!(
+                "Key {} can't both be expected absent and have a value",
+                entry.key
+            ));
+        }
+        let previous_value = storage_write(
+            &base64::decode(entry.key).unwrap(),
+            &base64::decode(entry.value).unwrap(),
+        );
+        if entry.expected_absent && previous_value.is_some() {
+            panic_str(&format!("Key {} is expected to be absent", entry.key));
+        }
+        if let Some(expected_value) = entry.expected_value {
+            if previous_value != Some(base64::decode(expected_value).unwrap()) {
+                panic_str(&format!(
+                    "Key {} doesn't have the expected value",
+                    entry.key
+                ));
+            }
+        }
+        overwritten.push(Overwritten {
+            key: entry.key,
+            previous_value: previous_value.map(base64::encode),
+        });
+    }
+    value_return(&serde_json::to_vec(&overwritten).unwrap());
+}
+
 #[cfg(feature = "clean")]
 #[no_mangle]
 pub fn clean() {
@@ -107,6 +211,86 @@ pub fn clean() {
     }
 }
 
+/// Gas kept to serialize and return the number of keys removed by `clean_prefix`.
+#[cfg(feature = "clean")]
+const CLEAN_PREFIX_GAS_RESERVE: u64 = 10_000_000_000_000;
+
+#[cfg(feature = "clean")]
+#[no_mangle]
+pub fn clean_prefix() {
+    #[derive(serde::Deserialize)]
+    struct CleanPrefixInput<'a> {
+        prefix: &'a str,
+        /// Keys of the batch relative to `prefix`, enumerated off-chain, because the runtime
+        /// doesn't support storage iteration.
+        #[serde(borrow)]
+        keys: Vec<&'a str>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct CleanPrefixOutput {
+        /// Number of keys removed from the start of `keys`, less than the number of keys if gas
+        /// ran low.
+        removed: u64,
+    }
+
+    let input = input().unwrap();
+    let args: CleanPrefixInput = serde_json::from_slice(&input).unwrap();
+    let mut key = base64::decode(args.prefix).unwrap();
+    let prefix_len = key.len();
+
+    let mut output = CleanPrefixOutput { removed: 0 };
+    for relative_key in args.keys {
+        if remaining_gas() <= CLEAN_PREFIX_GAS_RESERVE {
+            break;
+        }
+        key.truncate(prefix_len);
+        key.extend(base64::decode(relative_key).unwrap());
+        storage_remove(&key);
+        output.removed += 1;
+    }
+    value_return(&serde_json::to_vec(&output).unwrap());
+}
+
+#[cfg(feature = "dump")]
+#[no_mangle]
+pub fn dump() {
+    #[derive(serde::Deserialize)]
+    struct DumpInput<'a> {
+        prefix: &'a str,
+        /// Keys of the page relative to `prefix`, enumerated off-chain, because the runtime
+        /// doesn't support storage iteration.
+        #[serde(borrow)]
+        keys: Vec<&'a str>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct DumpOutput {
+        /// Key/value pairs in the format of `replace` input.
+        entries: Vec<(alloc::string::String, alloc::string::String)>,
+    }
+
+    let input = input().unwrap();
+    let args: DumpInput = serde_json::from_slice(&input).unwrap();
+    let mut key = base64::decode(args.prefix).unwrap();
+    let prefix_len = key.len();
+
+    let mut output = DumpOutput {
+        entries: Vec::with_capacity(args.keys.len()),
+    };
+    for relative_key in args.keys {
+        key.truncate(prefix_len);
+        key.extend(base64::decode(relative_key).unwrap());
+        // Keys that are not in the state are skipped.
+        if let Some(value) = storage_read(&key) {
+            output
+                .entries
+                .push((base64::encode(&key), base64::encode(value)));
+        }
+    }
+    value_return(&serde_json::to_vec(&output).unwrap());
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use arbitrary::{Arbitrary, Unstructured};
@@ -191,4 +375,226 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn workspaces_clean_prefix_test() -> anyhow::Result<()> {
+        let wasm = fs::read("res/state_manipulation.wasm").await?;
+
+        let worker = workspaces::sandbox().await?;
+
+        let contract = worker.dev_deploy(&wasm).await?;
+
+        let mut rng = XorShiftRng::seed_from_u64(8);
+        let mut buf = vec![0; BUFFER_SIZE];
+        let key_values = generate_n_elements(256, &mut rng, &mut buf);
+
+        let prefix = b"p".to_vec();
+        let b64_bytes: Vec<(_, _)> = key_values
+            .iter()
+            .map(|(k, v)| {
+                let key = [prefix.as_slice(), k.as_slice()].concat();
+                (base64::encode(key), base64::encode(v))
+            })
+            .chain(core::iter::once((
+                base64::encode("STATE"),
+                base64::encode("test"),
+            )))
+            .collect();
+        contract
+            .call(&worker, "replace")
+            .args_json(&serde_json::json!({ "entries": &b64_bytes }))?
+            .max_gas()
+            .transact()
+            .await?;
+
+        // Remove keys under the prefix in batches, resending the keys that weren't removed.
+        let keys: Vec<_> = key_values.keys().map(base64::encode).collect();
+        let mut index = 0;
+        while index < keys.len() {
+            let batch = &keys[index..core::cmp::min(index + 100, keys.len())];
+            let result: serde_json::Value = contract
+                .call(&worker, "clean_prefix")
+                .args_json(&serde_json::json!({
+                    "prefix": base64::encode(&prefix),
+                    "keys": batch,
+                }))?
+                .max_gas()
+                .transact()
+                .await?
+                .json()?;
+            let removed = result["removed"].as_u64().unwrap() as usize;
+            assert!(removed > 0);
+            index += removed;
+        }
+
+        // Only the key outside of the prefix is left.
+        let state_items = contract.view_state(&worker, None).await?;
+        assert_eq!(state_items.len(), 1);
+        assert_eq!(state_items.get(&b"STATE".to_vec()).unwrap(), b"test");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn workspaces_replace_checked_test() -> anyhow::Result<()> {
+        let wasm = fs::read("res/state_manipulation.wasm").await?;
+
+        let worker = workspaces::sandbox().await?;
+
+        let contract = worker.dev_deploy(&wasm).await?;
+
+        let key = base64::encode("STATE");
+        let entries = serde_json::json!({ "entries": [{
+            "key": &key,
+            "value": base64::encode("test"),
+            "expected_absent": true,
+        }]});
+        let result: serde_json::Value = contract
+            .call(&worker, "replace_checked")
+            .args_json(&entries)?
+            .max_gas()
+            .transact()
+            .await?
+            .json()?;
+        assert_eq!(
+            result,
+            serde_json::json!([{ "key": &key, "previous_value": null }])
+        );
+
+        // Running the same migration again fails and doesn't change the state.
+        let _ = contract
+            .call(&worker, "replace_checked")
+            .args_json(&serde_json::json!({ "entries": [{
+                "key": &key,
+                "value": base64::encode("other"),
+                "expected_absent": true,
+            }]}))?
+            .max_gas()
+            .transact()
+            .await;
+        let state_items = contract.view_state(&worker, None).await?;
+        assert_eq!(state_items.get(&b"STATE".to_vec()).unwrap(), b"test");
+
+        // Wrong expected value fails as well.
+        let _ = contract
+            .call(&worker, "replace_checked")
+            .args_json(&serde_json::json!({ "entries": [{
+                "key": &key,
+                "value": base64::encode("other"),
+                "expected_value": base64::encode("wrong"),
+            }]}))?
+            .max_gas()
+            .transact()
+            .await;
+        let state_items = contract.view_state(&worker, None).await?;
+        assert_eq!(state_items.get(&b"STATE".to_vec()).unwrap(), b"test");
+
+        let result: serde_json::Value = contract
+            .call(&worker, "replace_checked")
+            .args_json(&serde_json::json!({ "entries": [{
+                "key": &key,
+                "value": base64::encode("updated"),
+                "expected_value": base64::encode("test"),
+            }]}))?
+            .max_gas()
+            .transact()
+            .await?
+            .json()?;
+        assert_eq!(
+            result,
+            serde_json::json!([{ "key": &key, "previous_value": base64::encode("test") }])
+        );
+        let state_items = contract.view_state(&worker, None).await?;
+        assert_eq!(state_items.get(&b"STATE".to_vec()).unwrap(), b"updated");
+
+        Ok(())
+    }
+
+    // Dumps all keys under the prefix in pages of `limit` keys.
+    async fn dump_all(
+        worker: &Worker<impl DevNetwork>,
+        contract: &Contract,
+        prefix: &[u8],
+        keys: &[String],
+        limit: usize,
+    ) -> anyhow::Result<Vec<(String, String)>> {
+        let mut entries = Vec::new();
+        for page in keys.chunks(limit) {
+            let result: serde_json::Value = contract
+                .call(&worker, "dump")
+                .args_json(&serde_json::json!({
+                    "prefix": base64::encode(prefix),
+                    "keys": page,
+                }))?
+                .max_gas()
+                .transact()
+                .await?
+                .json()?;
+            let page: Vec<(String, String)> = serde_json::from_value(result["entries"].clone())?;
+            entries.extend(page);
+        }
+        Ok(entries)
+    }
+
+    #[tokio::test]
+    async fn workspaces_dump_test() -> anyhow::Result<()> {
+        let wasm = fs::read("res/state_manipulation.wasm").await?;
+
+        let worker = workspaces::sandbox().await?;
+
+        let contract = worker.dev_deploy(&wasm).await?;
+
+        for i in [8, 64, 256] {
+            let mut rng = XorShiftRng::seed_from_u64(8);
+            let mut buf = vec![0; BUFFER_SIZE];
+            let key_values = generate_n_elements(i, &mut rng, &mut buf);
+
+            let prefix = b"p".to_vec();
+            let b64_bytes: Vec<(_, _)> = key_values
+                .iter()
+                .map(|(k, v)| {
+                    let key = [prefix.as_slice(), k.as_slice()].concat();
+                    (base64::encode(key), base64::encode(v))
+                })
+                .collect();
+            contract
+                .call(&worker, "replace")
+                .args_json(&serde_json::json!({ "entries": &b64_bytes }))?
+                .max_gas()
+                .transact()
+                .await?;
+            let state_before = contract.view_state(&worker, None).await?;
+
+            // Back up the state, clean it and restore it from the backup.
+            let keys: Vec<_> = key_values.keys().map(base64::encode).collect();
+            let entries = dump_all(&worker, &contract, &prefix, &keys, 50).await?;
+            assert_eq!(entries.len(), key_values.len());
+
+            let full_keys: Vec<_> = b64_bytes.iter().map(|(k, _)| k.as_str()).collect();
+            contract
+                .call(&worker, "clean")
+                .args_json(&serde_json::json!({ "keys": &full_keys }))?
+                .max_gas()
+                .transact()
+                .await?;
+            assert!(contract.view_state(&worker, None).await?.is_empty());
+
+            contract
+                .call(&worker, "replace")
+                .args_json(&serde_json::json!({ "entries": &entries }))?
+                .max_gas()
+                .transact()
+                .await?;
+            assert_eq!(contract.view_state(&worker, None).await?, state_before);
+
+            contract
+                .call(&worker, "clean")
+                .args_json(&serde_json::json!({ "keys": &full_keys }))?
+                .max_gas()
+                .transact()
+                .await?;
+        }
+
+        Ok(())
+    }
 }
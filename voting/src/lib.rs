@@ -1,25 +1,376 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap, Vector};
 use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, near_bindgen, AccountId, Balance, EpochHeight};
+use std::cmp::min;
 use std::collections::HashMap;
+use uint::construct_uint;
 
 #[global_allocator]
 static ALLOC: near_sdk::wee_alloc::WeeAlloc = near_sdk::wee_alloc::WeeAlloc::INIT;
 
 type WrappedTimestamp = U64;
 
-/// Voting contract for unlocking transfers. Once the majority of the stake holders agree to
-/// unlock transfer, the time will be recorded and the voting ends.
+construct_uint! {
+    /// 256-bit unsigned integer.
+    pub struct U256(4);
+}
+
+/// Index of the proposal in the registry.
+pub type ProposalId = u64;
+
+/// The proposal to unlock transfers. It's created at initialization and it's the one used by
+/// `vote`, `ping`, `get_result` and other methods that don't take a proposal ID.
+pub const TRANSFERS_PROPOSAL_ID: ProposalId = 0;
+
+/// The maximum length of the proposal description, in bytes.
+const MAX_DESCRIPTION_LEN: usize = 1024;
+
+/// The number of votes recomputed by a single ping when the epoch changes, unless the caller
+/// gives a different limit. Keeps `vote` and `ping` within the gas limit for large validator sets.
+const DEFAULT_PING_LIMIT: u64 = 50;
+
+/// The vote of a validator on a proposal.
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Vote {
+    Yes,
+    No,
+    Abstain,
+    /// Splits the stake between `Yes`, `No` and `Abstain` pro rata to the given weights. Used by
+    /// staking pools to vote with the preferences of their delegators.
+    Split {
+        yes: U128,
+        no: U128,
+        abstain: U128,
+    },
+}
+
+impl Vote {
+    pub fn assert_valid(&self) {
+        if let Vote::Split { yes, no, .. } = self {
+            assert!(
+                yes.0 > 0 || no.0 > 0,
+                "The split vote should have a positive weight"
+            );
+        }
+    }
+
+    /// Returns the parts of the given stake that go to `Yes`, `No` and `Abstain`.
+    fn split_stake(&self, stake: Balance) -> (Balance, Balance, Balance) {
+        match self {
+            Vote::Yes => (stake, 0, 0),
+            Vote::No => (0, stake, 0),
+            Vote::Abstain => (0, 0, stake),
+            Vote::Split { yes, no, abstain } => {
+                let total_weight = U256::from(yes.0) + U256::from(no.0) + U256::from(abstain.0);
+                let yes_stake = (U256::from(stake) * U256::from(yes.0) / total_weight).as_u128();
+                let abstain_stake =
+                    (U256::from(stake) * U256::from(abstain.0) / total_weight).as_u128();
+                (yes_stake, stake - yes_stake - abstain_stake, abstain_stake)
+            }
+        }
+    }
+}
+
+/// The fraction of the total validator stake that has to vote `Yes` for a proposal to pass.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Threshold {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Threshold {
+    pub fn assert_valid(&self) {
+        assert!(
+            self.numerator > 0 && self.numerator < self.denominator,
+            "The threshold should be more than 0 and less than 1"
+        );
+    }
+
+    /// Returns `true` if the given stake is more than this fraction of the total stake.
+    fn is_exceeded_by(&self, stake: Balance, total_stake: Balance) -> bool {
+        let numerator = self.numerator as Balance;
+        let denominator = self.denominator as Balance;
+        // Computed in parts to avoid overflow on large total stake.
+        let required_stake = total_stake / denominator * numerator
+            + total_stake % denominator * numerator / denominator;
+        stake > required_stake
+    }
+}
+
+/// A proposal validators vote on.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Proposal {
+    /// The account ID that created the proposal.
+    pub proposer_id: AccountId,
+    /// Human readable description of the proposal.
+    pub description: String,
+    /// The block timestamp after which the proposal doesn't accept votes. `None` means the
+    /// proposal never expires.
+    pub expiration_timestamp: Option<u64>,
+    /// The fraction of the total stake that has to vote `Yes` for the proposal to pass.
+    pub threshold: Threshold,
+    /// The epoch height at which the voting power is fixed. `None` means validators vote with
+    /// their current stake.
+    pub snapshot_epoch_height: Option<EpochHeight>,
+    /// The stake of validators recorded at the snapshot epoch.
+    pub snapshot_stakes: LookupMap<AccountId, Balance>,
+    /// The total stake recorded at the snapshot epoch.
+    pub snapshot_total_stake: Balance,
+    /// How each validator voted and with how much stake.
+    pub votes: UnorderedMap<AccountId, (Vote, Balance)>,
+    /// Total stake voted `Yes` so far.
+    pub yes_stake: Balance,
+    /// Total stake voted `No` so far.
+    pub no_stake: Balance,
+    /// Total stake voted `Abstain` so far.
+    pub abstain_stake: Balance,
+    /// When the proposal passed. `None` means the voting is still open or the proposal expired.
+    pub result: Option<WrappedTimestamp>,
+    /// Epoch height for which the stake of all votes was last recomputed.
+    pub last_epoch_height: EpochHeight,
+    /// Epoch height for which the stake of votes is being recomputed.
+    pub ping_epoch_height: EpochHeight,
+    /// The number of votes left to recompute for `ping_epoch_height`. Votes are recomputed from
+    /// the end of `votes`, so the ones at this index and above are already up to date.
+    pub ping_cursor: u64,
+}
+
+impl Proposal {
+    pub fn new(
+        id: ProposalId,
+        proposer_id: AccountId,
+        description: String,
+        expiration_timestamp: Option<u64>,
+        threshold: Threshold,
+        snapshot_epoch_height: Option<EpochHeight>,
+    ) -> Self {
+        Self {
+            proposer_id,
+            description,
+            expiration_timestamp,
+            threshold,
+            snapshot_epoch_height,
+            snapshot_stakes: LookupMap::new([b"s".as_ref(), &id.to_le_bytes()].concat()),
+            snapshot_total_stake: 0,
+            votes: UnorderedMap::new([b"v".as_ref(), &id.to_le_bytes()].concat()),
+            yes_stake: 0,
+            no_stake: 0,
+            abstain_stake: 0,
+            result: None,
+            last_epoch_height: 0,
+            ping_epoch_height: 0,
+            ping_cursor: 0,
+        }
+    }
+
+    /// Returns `true` if the proposal doesn't accept votes anymore because of the expiration.
+    pub fn is_expired(&self) -> bool {
+        self.result.is_none()
+            && self
+                .expiration_timestamp
+                .map(|timestamp| env::block_timestamp() > timestamp)
+                .unwrap_or(false)
+    }
+
+    /// Returns `true` if the stake of all votes is final for the current epoch. In snapshot mode
+    /// votes never have to be recomputed.
+    pub fn is_up_to_date(&self) -> bool {
+        self.snapshot_epoch_height.is_some() || self.last_epoch_height == env::epoch_height()
+    }
+
+    /// Updates up to `limit` votes according to current stake of validators. Once the stake of
+    /// all votes is recomputed for the current epoch, checks whether the proposal has passed.
+    /// Returns `true` if all votes are up to date.
+    pub fn ping(&mut self, limit: u64) -> bool {
+        assert!(self.result.is_none(), "Voting has already ended");
+        assert!(!self.is_expired(), "The proposal has expired");
+        if self.is_up_to_date() {
+            return true;
+        }
+        let cur_epoch_height = env::epoch_height();
+        if cur_epoch_height != self.ping_epoch_height {
+            self.ping_epoch_height = cur_epoch_height;
+            self.ping_cursor = self.votes.len();
+        }
+        for _ in 0..limit {
+            if self.ping_cursor == 0 {
+                break;
+            }
+            self.ping_cursor -= 1;
+            let account_id = self.votes.keys_as_vector().get(self.ping_cursor).unwrap();
+            let (vote, voted_stake) = self.votes.values_as_vector().get(self.ping_cursor).unwrap();
+            self.remove_stake(vote, voted_stake);
+            let account_current_stake = env::validator_stake(&account_id);
+            if account_current_stake > 0 {
+                self.add_stake(vote, account_current_stake);
+                self.votes
+                    .insert(&account_id, &(vote, account_current_stake));
+            } else {
+                // The last vote, which is already up to date, takes the place of the removed one.
+                self.votes.remove(&account_id);
+            }
+        }
+        if self.ping_cursor > 0 {
+            return false;
+        }
+        self.last_epoch_height = cur_epoch_height;
+        self.check_result();
+        true
+    }
+
+    /// Votes with the current stake of the given validator, or the stake at the snapshot epoch in
+    /// snapshot mode. Withdraws the vote if `vote` is `None`.
+    pub fn vote(&mut self, account_id: AccountId, vote: Option<Vote>) {
+        self.ping(DEFAULT_PING_LIMIT);
+        if self.result.is_some() {
+            return;
+        }
+        if let Some((previous_vote, voted_stake)) = self.votes.remove(&account_id) {
+            self.remove_stake(previous_vote, voted_stake);
+            // The last vote took the place of the removed one. If it wasn't recomputed yet, it's
+            // still below the cursor.
+            self.ping_cursor = min(self.ping_cursor, self.votes.len());
+        }
+        if let Some(vote) = vote {
+            vote.assert_valid();
+            let account_stake = self.voting_stake(&account_id);
+            assert!(account_stake > 0, "{} is not a validator", account_id);
+            self.add_stake(vote, account_stake);
+            // New votes are added to the end, so they are not recomputed again in this epoch.
+            self.votes.insert(&account_id, &(vote, account_stake));
+            // The result is only checked when all votes have the stake of the current epoch.
+            if self.is_up_to_date() {
+                self.check_result();
+            }
+        }
+    }
+
+    /// Records the current stake of the given validator and the total stake if the current epoch
+    /// is the snapshot epoch.
+    pub fn record_snapshot_stake(&mut self, account_id: &AccountId) {
+        if self.snapshot_epoch_height != Some(env::epoch_height()) {
+            return;
+        }
+        self.snapshot_total_stake = env::validator_total_stake();
+        let stake = env::validator_stake(account_id);
+        if stake > 0 {
+            self.snapshot_stakes.insert(account_id, &stake);
+        }
+    }
+
+    /// Returns the stake the given validator votes with.
+    fn voting_stake(&mut self, account_id: &AccountId) -> Balance {
+        match self.snapshot_epoch_height {
+            None => env::validator_stake(account_id),
+            Some(snapshot_epoch_height) => {
+                assert!(
+                    env::epoch_height() >= snapshot_epoch_height,
+                    "The voting starts at the snapshot epoch {}",
+                    snapshot_epoch_height
+                );
+                self.record_snapshot_stake(account_id);
+                self.snapshot_stakes.get(account_id).unwrap_or(0)
+            }
+        }
+    }
+
+    /// Returns the total stake the threshold applies to.
+    fn total_stake(&self) -> Balance {
+        if self.snapshot_epoch_height.is_some() {
+            self.snapshot_total_stake
+        } else {
+            env::validator_total_stake()
+        }
+    }
+
+    /// Returns votes using pagination.
+    pub fn get_votes(&self, from_index: u64, limit: u64) -> Vec<(AccountId, (Vote, Balance))> {
+        let keys = self.votes.keys_as_vector();
+        let values = self.votes.values_as_vector();
+        (from_index..min(from_index.saturating_add(limit), keys.len()))
+            .map(|index| (keys.get(index).unwrap(), values.get(index).unwrap()))
+            .collect()
+    }
+
+    /// Check whether the proposal has passed.
+    fn check_result(&mut self) {
+        assert!(
+            self.result.is_none(),
+            "check result is called after result is already set"
+        );
+        if self
+            .threshold
+            .is_exceeded_by(self.yes_stake, self.total_stake())
+        {
+            self.result = Some(U64::from(env::block_timestamp()));
+        }
+    }
+
+    fn add_stake(&mut self, vote: Vote, voted_stake: Balance) {
+        let (yes_stake, no_stake, abstain_stake) = vote.split_stake(voted_stake);
+        self.yes_stake += yes_stake;
+        self.no_stake += no_stake;
+        self.abstain_stake += abstain_stake;
+    }
+
+    fn remove_stake(&mut self, vote: Vote, voted_stake: Balance) {
+        let (yes_stake, no_stake, abstain_stake) = vote.split_stake(voted_stake);
+        for (stake, voted_stake) in vec![
+            (&mut self.yes_stake, yes_stake),
+            (&mut self.no_stake, no_stake),
+            (&mut self.abstain_stake, abstain_stake),
+        ] {
+            assert!(
+                voted_stake <= *stake,
+                "invariant: voted stake {} is more than total voted stake {}",
+                voted_stake,
+                *stake
+            );
+            *stake -= voted_stake;
+        }
+    }
+}
+
+/// Proposal information returned by view methods.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableProposal {
+    pub id: ProposalId,
+    pub proposer_id: AccountId,
+    pub description: String,
+    pub expiration_timestamp: Option<WrappedTimestamp>,
+    pub threshold: Threshold,
+    pub snapshot_epoch_height: Option<EpochHeight>,
+    pub yes_stake: U128,
+    pub no_stake: U128,
+    pub abstain_stake: U128,
+    pub num_votes: u64,
+    pub result: Option<WrappedTimestamp>,
+    pub is_expired: bool,
+}
+
+/// Voting contract for proposals voted by validators. The proposal `0` is created at
+/// initialization to unlock transfers. Once the stake that voted for a proposal exceeds its
+/// threshold, the time will be recorded and the voting on that proposal ends.
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct VotingContract {
-    /// How much each validator votes
+    /// All proposals by their IDs.
+    proposals: Vector<Proposal>,
+}
+
+/// State of the contract before proposals were introduced. It only had the transfers poll.
+#[derive(BorshDeserialize)]
+struct OldVotingContract {
     votes: HashMap<AccountId, Balance>,
-    /// Total voted balance so far.
     total_voted_stake: Balance,
-    /// When the voting ended. `None` means the poll is still open.
     result: Option<WrappedTimestamp>,
-    /// Epoch height when the contract is touched last time.
     last_epoch_height: EpochHeight,
 }
 
@@ -34,100 +385,285 @@ impl VotingContract {
     #[init]
     pub fn new() -> Self {
         assert!(!env::state_exists(), "The contract is already initialized");
-        VotingContract {
-            votes: HashMap::new(),
-            total_voted_stake: 0,
-            result: None,
-            last_epoch_height: 0,
-        }
+        let mut contract = Self {
+            proposals: Vector::new(b"p".to_vec()),
+        };
+        contract.proposals.push(&Self::transfers_proposal());
+        contract
     }
 
-    /// Ping to update the votes according to current stake of validators.
-    pub fn ping(&mut self) {
-        assert!(self.result.is_none(), "Voting has already ended");
-        let cur_epoch_height = env::epoch_height();
-        if cur_epoch_height != self.last_epoch_height {
-            let votes = std::mem::take(&mut self.votes);
-            self.total_voted_stake = 0;
-            for (account_id, _) in votes {
-                let account_current_stake = env::validator_stake(&account_id);
-                self.total_voted_stake += account_current_stake;
-                if account_current_stake > 0 {
-                    self.votes.insert(account_id, account_current_stake);
-                }
-            }
-            self.check_result();
-            self.last_epoch_height = cur_epoch_height;
+    /// Migrates the state of the contract deployed before proposals were introduced. The existing
+    /// poll becomes the proposal `0`. Can only be called by the contract itself.
+    #[init]
+    pub fn migrate() -> Self {
+        assert_eq!(
+            env::predecessor_account_id(),
+            env::current_account_id(),
+            "Can only be called by the contract itself"
+        );
+        let old: OldVotingContract = env::state_read().expect("The contract is not initialized");
+        let mut proposal = Self::transfers_proposal();
+        for (account_id, stake) in old.votes {
+            proposal.votes.insert(&account_id, &(Vote::Yes, stake));
         }
+        proposal.yes_stake = old.total_voted_stake;
+        proposal.result = old.result;
+        proposal.last_epoch_height = old.last_epoch_height;
+        proposal.ping_epoch_height = old.last_epoch_height;
+        let mut contract = Self {
+            proposals: Vector::new(b"p".to_vec()),
+        };
+        contract.proposals.push(&proposal);
+        contract
     }
 
-    /// Check whether the voting has ended.
-    fn check_result(&mut self) {
+    /// Creates a new proposal and returns its ID. Can only be called by a validator.
+    /// - `expiration_timestamp` - the block timestamp after which the proposal doesn't accept
+    ///    votes. The proposal never expires if not given.
+    /// - `threshold` - the fraction of the total stake that has to vote `Yes` for the proposal to
+    ///    pass. 2/3 if not given.
+    /// - `snapshot_epoch_height` - the epoch at which the voting power of validators is fixed.
+    ///    The voting starts at this epoch and validators vote with the stake they had in it.
+    ///    Validators vote with their current stake if not given.
+    pub fn create_proposal(
+        &mut self,
+        description: String,
+        expiration_timestamp: Option<WrappedTimestamp>,
+        threshold: Option<Threshold>,
+        snapshot_epoch_height: Option<EpochHeight>,
+    ) -> ProposalId {
+        let proposer_id = env::predecessor_account_id();
         assert!(
-            self.result.is_none(),
-            "check result is called after result is already set"
+            env::validator_stake(&proposer_id) > 0,
+            "{} is not a validator",
+            proposer_id
         );
-        let total_stake = env::validator_total_stake();
-        if self.total_voted_stake > 2 * total_stake / 3 {
-            self.result = Some(U64::from(env::block_timestamp()));
+        assert!(
+            description.len() <= MAX_DESCRIPTION_LEN,
+            "The description can't be longer than {} bytes",
+            MAX_DESCRIPTION_LEN
+        );
+        let expiration_timestamp = expiration_timestamp.map(|timestamp| timestamp.0);
+        if let Some(timestamp) = expiration_timestamp {
+            assert!(
+                timestamp > env::block_timestamp(),
+                "The expiration timestamp should be in the future"
+            );
+        }
+        let threshold = threshold.unwrap_or_else(Self::default_threshold);
+        threshold.assert_valid();
+        if let Some(epoch_height) = snapshot_epoch_height {
+            assert!(
+                epoch_height >= env::epoch_height(),
+                "The snapshot epoch height can't be in the past"
+            );
+        }
+        let mut proposal = Proposal::new(
+            self.proposals.len(),
+            proposer_id,
+            description,
+            expiration_timestamp,
+            threshold,
+            snapshot_epoch_height,
+        );
+        proposal.record_snapshot_stake(&env::predecessor_account_id());
+        self.proposals.push(&proposal);
+        self.proposals.len() - 1
+    }
+
+    /// Ping to update the votes on the given proposal according to current stake of validators.
+    /// Recomputes at most `limit` votes, 50 if not given. Returns `true` if all votes are up to
+    /// date, otherwise the method should be called again.
+    pub fn ping_proposal(&mut self, proposal_id: ProposalId, limit: Option<u64>) -> bool {
+        let mut proposal = self.internal_get_proposal(proposal_id);
+        let is_up_to_date = proposal.ping(limit.unwrap_or(DEFAULT_PING_LIMIT));
+        self.proposals.replace(proposal_id, &proposal);
+        is_up_to_date
+    }
+
+    /// Records the stake of the given validators for the proposal in snapshot mode. Can be called
+    /// by anyone during the snapshot epoch, so validators can vote with this stake later.
+    pub fn record_snapshot_stakes(&mut self, proposal_id: ProposalId, account_ids: Vec<AccountId>) {
+        let mut proposal = self.internal_get_proposal(proposal_id);
+        assert_eq!(
+            proposal.snapshot_epoch_height,
+            Some(env::epoch_height()),
+            "The stake can only be recorded during the snapshot epoch"
+        );
+        for account_id in account_ids {
+            proposal.record_snapshot_stake(&account_id);
         }
+        self.proposals.replace(proposal_id, &proposal);
+    }
+
+    /// Method for validators to vote on the given proposal with their current stake, or the stake
+    /// at the snapshot epoch in snapshot mode. `Split` vote divides the stake between `Yes`, `No`
+    /// and `Abstain`, e.g. by the preferences of delegators of a staking pool.
+    pub fn vote_on_proposal(&mut self, proposal_id: ProposalId, vote: Vote) {
+        let mut proposal = self.internal_get_proposal(proposal_id);
+        proposal.vote(env::predecessor_account_id(), Some(vote));
+        self.proposals.replace(proposal_id, &proposal);
     }
 
-    /// Method for validators to vote or withdraw the vote.
+    /// Method for validators to withdraw their vote on the given proposal.
+    pub fn withdraw_vote(&mut self, proposal_id: ProposalId) {
+        let mut proposal = self.internal_get_proposal(proposal_id);
+        proposal.vote(env::predecessor_account_id(), None);
+        self.proposals.replace(proposal_id, &proposal);
+    }
+
+    /// Ping to update the votes on the transfers proposal according to current stake of
+    /// validators. Returns `true` if all votes are up to date, otherwise the method should be
+    /// called again.
+    pub fn ping(&mut self) -> bool {
+        self.ping_proposal(TRANSFERS_PROPOSAL_ID, None)
+    }
+
+    /// Method for validators to vote or withdraw the vote on the transfers proposal.
     /// Votes for if `is_vote` is true, or withdraws the vote if `is_vote` is false.
     pub fn vote(&mut self, is_vote: bool) {
-        self.ping();
-        if self.result.is_some() {
-            return;
-        }
-        let account_id = env::predecessor_account_id();
-        let account_stake = if is_vote {
-            let stake = env::validator_stake(&account_id);
-            assert!(stake > 0, "{} is not a validator", account_id);
-            stake
-        } else {
-            0
-        };
-        let voted_stake = self.votes.remove(&account_id).unwrap_or_default();
-        assert!(
-            voted_stake <= self.total_voted_stake,
-            "invariant: voted stake {} is more than total voted stake {}",
-            voted_stake,
-            self.total_voted_stake
+        let mut proposal = self.internal_get_proposal(TRANSFERS_PROPOSAL_ID);
+        proposal.vote(
+            env::predecessor_account_id(),
+            if is_vote { Some(Vote::Yes) } else { None },
         );
-        self.total_voted_stake = self.total_voted_stake + account_stake - voted_stake;
-        if account_stake > 0 {
-            self.votes.insert(account_id, account_stake);
-            self.check_result();
-        }
+        self.proposals.replace(TRANSFERS_PROPOSAL_ID, &proposal);
     }
 
-    /// Get the timestamp of when the voting finishes. `None` means the voting hasn't ended yet.
+    /// Get the timestamp of when the voting on the transfers proposal finishes. `None` means the
+    /// voting hasn't ended yet.
     pub fn get_result(&self) -> Option<WrappedTimestamp> {
-        self.result.clone()
+        self.get_proposal_result(TRANSFERS_PROPOSAL_ID)
     }
 
-    /// Returns current a pair of `total_voted_stake` and the total stake.
+    /// Returns current a pair of `total_voted_stake` and the total stake for the transfers
+    /// proposal.
     /// Note: as a view method, it doesn't recompute the active stake. May need to call `ping` to
     /// update the active stake.
     pub fn get_total_voted_stake(&self) -> (U128, U128) {
         (
-            self.total_voted_stake.into(),
+            self.internal_get_proposal(TRANSFERS_PROPOSAL_ID)
+                .yes_stake
+                .into(),
             env::validator_total_stake().into(),
         )
     }
 
-    /// Returns all active votes.
+    /// Returns active votes on the transfers proposal using pagination. Returns all votes if
+    /// `from_index` and `limit` are not given.
     /// Note: as a view method, it doesn't recompute the active stake. May need to call `ping` to
     /// update the active stake.
-    pub fn get_votes(&self) -> HashMap<AccountId, U128> {
-        self.votes
-            .iter()
-            .map(|(account_id, stake)| (account_id.clone(), (*stake).into()))
+    pub fn get_votes(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> HashMap<AccountId, U128> {
+        self.internal_get_proposal(TRANSFERS_PROPOSAL_ID)
+            .get_votes(from_index.unwrap_or(0), limit.unwrap_or(u64::MAX))
+            .into_iter()
+            .map(|(account_id, (_, stake))| (account_id, stake.into()))
+            .collect()
+    }
+
+    /// Get the timestamp of when the given proposal passed. `None` means the voting hasn't ended
+    /// yet or the proposal expired.
+    pub fn get_proposal_result(&self, proposal_id: ProposalId) -> Option<WrappedTimestamp> {
+        self.internal_get_proposal(proposal_id).result
+    }
+
+    /// Returns the number of proposals created including the transfers proposal.
+    pub fn get_number_of_proposals(&self) -> u64 {
+        self.proposals.len()
+    }
+
+    /// Returns the given proposal.
+    /// Note: as a view method, it doesn't recompute the active stake. May need to call
+    /// `ping_proposal` to update the active stake.
+    pub fn get_proposal(&self, proposal_id: ProposalId) -> Option<HumanReadableProposal> {
+        self.proposals
+            .get(proposal_id)
+            .map(|proposal| Self::to_human_readable(proposal_id, proposal))
+    }
+
+    /// Returns proposals using pagination.
+    pub fn get_proposals(&self, from_index: u64, limit: u64) -> Vec<HumanReadableProposal> {
+        (from_index..min(from_index.saturating_add(limit), self.proposals.len()))
+            .map(|proposal_id| {
+                Self::to_human_readable(proposal_id, self.proposals.get(proposal_id).unwrap())
+            })
+            .collect()
+    }
+
+    /// Returns the stake of the given validator recorded at the snapshot epoch of the proposal.
+    pub fn get_snapshot_stake(&self, proposal_id: ProposalId, account_id: AccountId) -> U128 {
+        self.internal_get_proposal(proposal_id)
+            .snapshot_stakes
+            .get(&account_id)
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Returns active votes on the given proposal with the voted stake using pagination.
+    /// Note: as a view method, it doesn't recompute the active stake. May need to call
+    /// `ping_proposal` to update the active stake.
+    pub fn get_proposal_votes(
+        &self,
+        proposal_id: ProposalId,
+        from_index: u64,
+        limit: u64,
+    ) -> HashMap<AccountId, (Vote, U128)> {
+        self.internal_get_proposal(proposal_id)
+            .get_votes(from_index, limit)
+            .into_iter()
+            .map(|(account_id, (vote, stake))| (account_id, (vote, stake.into())))
             .collect()
     }
 }
 
+impl VotingContract {
+    /// 2/3 of the total stake, the threshold of the transfers proposal.
+    fn default_threshold() -> Threshold {
+        Threshold {
+            numerator: 2,
+            denominator: 3,
+        }
+    }
+
+    fn transfers_proposal() -> Proposal {
+        Proposal::new(
+            TRANSFERS_PROPOSAL_ID,
+            env::current_account_id(),
+            "Unlock token transfers".to_string(),
+            None,
+            Self::default_threshold(),
+            None,
+        )
+    }
+
+    fn internal_get_proposal(&self, proposal_id: ProposalId) -> Proposal {
+        self.proposals.get(proposal_id).expect("No such proposal")
+    }
+
+    fn to_human_readable(proposal_id: ProposalId, proposal: Proposal) -> HumanReadableProposal {
+        HumanReadableProposal {
+            id: proposal_id,
+            is_expired: proposal.is_expired(),
+            proposer_id: proposal.proposer_id,
+            description: proposal.description,
+            expiration_timestamp: proposal
+                .expiration_timestamp
+                .map(|timestamp| timestamp.into()),
+            threshold: proposal.threshold,
+            snapshot_epoch_height: proposal.snapshot_epoch_height,
+            yes_stake: proposal.yes_stake.into(),
+            no_stake: proposal.no_stake.into(),
+            abstain_stake: proposal.abstain_stake.into(),
+            num_votes: proposal.votes.len(),
+            result: proposal.result,
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -189,7 +725,7 @@ mod tests {
         testing_env!(context, Default::default(), Default::default(), validators);
         let mut contract = VotingContract::new();
         contract.vote(true);
-        assert!(contract.result.is_some());
+        assert!(contract.get_result().is_some());
         contract.vote(true);
     }
 
@@ -228,16 +764,16 @@ mod tests {
                 (U128::from(10 * (i + 1)), U128::from(100))
             );
             assert_eq!(
-                contract.get_votes(),
+                contract.get_votes(None, None),
                 (0..=i)
                     .map(|i| (format!("test{}", i), U128::from(10)))
                     .collect::<HashMap<_, _>>()
             );
-            assert_eq!(contract.votes.len() as u128, i + 1);
+            assert_eq!(contract.get_votes(None, None).len() as u128, i + 1);
             if i < 6 {
-                assert!(contract.result.is_none());
+                assert!(contract.get_result().is_none());
             } else {
-                assert!(contract.result.is_some());
+                assert!(contract.get_result().is_some());
             }
         }
     }
@@ -265,11 +801,11 @@ mod tests {
                 validators.clone()
             );
             contract.vote(true);
-            assert_eq!(contract.votes.len() as u64, i + 1);
+            assert_eq!(contract.get_votes(None, None).len() as u64, i + 1);
             if i < 6 {
-                assert!(contract.result.is_none());
+                assert!(contract.get_result().is_none());
             } else {
-                assert!(contract.result.is_some());
+                assert!(contract.get_result().is_some());
             }
         }
     }
@@ -300,7 +836,7 @@ mod tests {
             validators.clone()
         );
         contract.ping();
-        assert!(contract.result.is_some());
+        assert!(contract.get_result().is_some());
     }
 
     #[test]
@@ -316,7 +852,7 @@ mod tests {
         );
         let mut contract = VotingContract::new();
         contract.vote(true);
-        assert_eq!(contract.votes.len(), 1);
+        assert_eq!(contract.get_votes(None, None).len(), 1);
         let context = get_context_with_epoch_height("test1".to_string(), 2);
         testing_env!(
             context,
@@ -325,7 +861,7 @@ mod tests {
             validators.clone()
         );
         contract.vote(false);
-        assert!(contract.votes.is_empty());
+        assert!(contract.get_votes(None, None).is_empty());
     }
 
     #[test]
@@ -357,4 +893,533 @@ mod tests {
         contract.ping();
         assert_eq!((contract.get_total_voted_stake().0).0, 0);
     }
+
+    #[test]
+    fn test_proposal_votes() {
+        let validators = (0..4)
+            .map(|i| (format!("test{}", i), 10))
+            .collect::<HashMap<_, _>>();
+        let context = get_context("test0".to_string());
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        let proposal_id = contract.create_proposal(
+            "Upgrade the protocol".to_string(),
+            None,
+            Some(Threshold {
+                numerator: 1,
+                denominator: 2,
+            }),
+            None,
+        );
+        assert_eq!(proposal_id, 1);
+        assert_eq!(contract.get_number_of_proposals(), 2);
+
+        for (i, vote) in vec![Vote::No, Vote::Abstain, Vote::Yes, Vote::Yes]
+            .into_iter()
+            .enumerate()
+        {
+            let context = get_context(format!("test{}", i));
+            testing_env!(
+                context,
+                Default::default(),
+                Default::default(),
+                validators.clone()
+            );
+            contract.vote_on_proposal(proposal_id, vote);
+        }
+        let proposal = contract.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.yes_stake, U128::from(20));
+        assert_eq!(proposal.no_stake, U128::from(10));
+        assert_eq!(proposal.abstain_stake, U128::from(10));
+        // Exactly half of the stake voted yes, it's not enough.
+        assert!(proposal.result.is_none());
+        assert_eq!(
+            contract.get_proposal_votes(proposal_id, 0, 10).get("test3"),
+            Some(&(Vote::Yes, U128::from(10)))
+        );
+
+        // Changing the vote from no to yes passes the proposal.
+        let context = get_context("test0".to_string());
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        contract.vote_on_proposal(proposal_id, Vote::Yes);
+        let proposal = contract.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.yes_stake, U128::from(30));
+        assert_eq!(proposal.no_stake, U128::from(0));
+        assert!(proposal.result.is_some());
+
+        // The transfers proposal is independent.
+        assert!(contract.get_result().is_none());
+        assert!(contract.get_votes(None, None).is_empty());
+        let transfers_proposal = contract.get_proposal(TRANSFERS_PROPOSAL_ID).unwrap();
+        assert_eq!(
+            transfers_proposal.threshold,
+            Threshold {
+                numerator: 2,
+                denominator: 3
+            }
+        );
+        assert_eq!(contract.get_proposals(0, 10).len(), 2);
+    }
+
+    #[test]
+    fn test_withdraw_proposal_vote() {
+        let validators =
+            HashMap::from_iter(vec![("test1".to_string(), 10), ("test2".to_string(), 10)]);
+        let context = get_context("test1".to_string());
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        let proposal_id = contract.create_proposal("Proposal".to_string(), None, None, None);
+        contract.vote_on_proposal(proposal_id, Vote::No);
+        assert_eq!(contract.get_proposal(proposal_id).unwrap().no_stake.0, 10);
+        contract.withdraw_vote(proposal_id);
+        assert_eq!(contract.get_proposal(proposal_id).unwrap().no_stake.0, 0);
+        assert!(contract.get_proposal_votes(proposal_id, 0, 10).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal has expired")]
+    fn test_vote_on_expired_proposal() {
+        let validators = HashMap::from_iter(vec![("test1".to_string(), 10)]);
+        let mut context = get_context("test1".to_string());
+        testing_env!(
+            context.clone(),
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        let proposal_id =
+            contract.create_proposal("Proposal".to_string(), Some(U64::from(100)), None, None);
+
+        context.block_timestamp = 101;
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        assert!(contract.get_proposal(proposal_id).unwrap().is_expired);
+        contract.vote_on_proposal(proposal_id, Vote::Yes);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a validator")]
+    fn test_nonvalidator_cannot_create_proposal() {
+        let context = get_context("bob.near".to_string());
+        let validators = HashMap::from_iter(vec![("alice.near".to_string(), 100)].into_iter());
+        testing_env!(context, Default::default(), Default::default(), validators);
+        let mut contract = VotingContract::new();
+        contract.create_proposal("Proposal".to_string(), None, None, None);
+    }
+
+    /// Sum of the stake of all votes on the transfers proposal read with pagination.
+    fn get_total_stake_of_votes(contract: &VotingContract, page_size: u64) -> Balance {
+        let mut total_stake = 0;
+        let mut from_index = 0;
+        loop {
+            let votes = contract.get_votes(Some(from_index), Some(page_size));
+            if votes.is_empty() {
+                return total_stake;
+            }
+            from_index += votes.len() as u64;
+            total_stake += votes.values().map(|stake| stake.0).sum::<Balance>();
+        }
+    }
+
+    #[test]
+    fn test_ping_large_validator_set() {
+        let num_validators = 300;
+        let mut validators = (0..num_validators)
+            .map(|i| (format!("test{}", i), 10))
+            .collect::<HashMap<_, _>>();
+        let context = get_context_with_epoch_height("test0".to_string(), 1);
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        // Half of the validators vote. It's not enough to pass.
+        for i in 0..num_validators / 2 {
+            let context = get_context_with_epoch_height(format!("test{}", i), 1);
+            testing_env!(
+                context,
+                Default::default(),
+                Default::default(),
+                validators.clone()
+            );
+            contract.vote(true);
+        }
+        assert!(contract.get_result().is_none());
+        assert_eq!(
+            contract.get_total_voted_stake(),
+            (U128::from(1500), U128::from(3000))
+        );
+        let votes = contract.get_votes(Some(100), Some(100));
+        assert_eq!(votes.len(), 50);
+        assert_eq!(votes.get("test149"), Some(&U128::from(10)));
+        assert_eq!(get_total_stake_of_votes(&contract, 7), 1500);
+
+        // The stake of voted validators grows and a few of them are kicked out. It's enough to
+        // pass, but the votes are recomputed over several pings.
+        for i in 0..num_validators / 2 {
+            validators.insert(format!("test{}", i), 30);
+        }
+        for i in 0..10 {
+            validators.remove(&format!("test{}", i * 10));
+        }
+        let mut context = get_context_with_epoch_height("test0".to_string(), 2);
+        // The mocked storage usage isn't carried between contexts, but the removed votes
+        // release the storage used by all the previous calls.
+        context.storage_usage = 1_000_000;
+        // Each ping is a separate transaction with its own gas.
+        let ping = |contract: &mut VotingContract| {
+            testing_env!(
+                context.clone(),
+                Default::default(),
+                Default::default(),
+                validators.clone()
+            );
+            contract.ping()
+        };
+        assert!(!ping(&mut contract));
+        assert!(!ping(&mut contract));
+        assert!(contract.get_result().is_none());
+        assert!(ping(&mut contract));
+        assert!(contract.get_result().is_some());
+        assert_eq!(contract.get_votes(None, None).len(), 140);
+        assert_eq!((contract.get_total_voted_stake().0).0, 140 * 30);
+        assert_eq!(get_total_stake_of_votes(&contract, 9), 140 * 30);
+    }
+
+    #[test]
+    fn test_vote_during_ping() {
+        let num_validators = 400;
+        let mut validators = (0..num_validators)
+            .map(|i| (format!("test{}", i), 10))
+            .collect::<HashMap<_, _>>();
+        let context = get_context_with_epoch_height("test0".to_string(), 1);
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        for i in 0..250 {
+            let context = get_context_with_epoch_height(format!("test{}", i), 1);
+            testing_env!(
+                context,
+                Default::default(),
+                Default::default(),
+                validators.clone()
+            );
+            contract.vote(true);
+        }
+        for i in 0..num_validators {
+            validators.insert(format!("test{}", i), 20);
+        }
+
+        // The first ping only recomputes the last votes.
+        let context = get_context_with_epoch_height("test0".to_string(), 2);
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        assert!(!contract.ping_proposal(TRANSFERS_PROPOSAL_ID, Some(30)));
+
+        // Every vote recomputes a part of votes, so these come in the middle of the ping and
+        // touch both recomputed and not yet recomputed votes.
+        for (account_id, is_vote) in vec![
+            ("test5", false),
+            ("test300", true),
+            ("test200", false),
+            ("test249", false),
+            ("test7", false),
+            ("test7", true),
+        ] {
+            let context = get_context_with_epoch_height(account_id.to_string(), 2);
+            testing_env!(
+                context,
+                Default::default(),
+                Default::default(),
+                validators.clone()
+            );
+            contract.vote(is_vote);
+        }
+        while !contract.ping_proposal(TRANSFERS_PROPOSAL_ID, Some(10)) {}
+
+        let votes = contract.get_votes(None, None);
+        assert_eq!(votes.len(), 248);
+        assert!(votes.values().all(|stake| stake.0 == 20));
+        assert!(!votes.contains_key("test5"));
+        assert!(votes.contains_key("test7"));
+        assert!(votes.contains_key("test300"));
+        assert_eq!((contract.get_total_voted_stake().0).0, 248 * 20);
+        assert_eq!(get_total_stake_of_votes(&contract, 11), 248 * 20);
+        // 4960 of 8000 is not enough to pass.
+        assert!(contract.get_result().is_none());
+    }
+
+    #[test]
+    fn test_snapshot_mode() {
+        let mut validators = (0..4)
+            .map(|i| (format!("test{}", i), 10))
+            .collect::<HashMap<_, _>>();
+        let context = get_context_with_epoch_height("test0".to_string(), 1);
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        let proposal_id = contract.create_proposal("Proposal".to_string(), None, None, Some(2));
+
+        // Validators vote or anyone records their stake during the snapshot epoch.
+        let context = get_context_with_epoch_height("test1".to_string(), 2);
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        contract.vote_on_proposal(proposal_id, Vote::Yes);
+        contract
+            .record_snapshot_stakes(proposal_id, vec!["test2".to_string(), "test3".to_string()]);
+        assert_eq!(
+            contract
+                .get_snapshot_stake(proposal_id, "test2".to_string())
+                .0,
+            10
+        );
+        assert_eq!(
+            contract
+                .get_snapshot_stake(proposal_id, "test0".to_string())
+                .0,
+            0
+        );
+
+        // The stake changes after the snapshot epoch don't affect the voting power.
+        validators.insert("test2".to_string(), 100);
+        validators.remove(&"test1".to_string());
+        for (i, account_id) in vec!["test2", "test3"].into_iter().enumerate() {
+            let context = get_context_with_epoch_height(account_id.to_string(), 3);
+            testing_env!(
+                context,
+                Default::default(),
+                Default::default(),
+                validators.clone()
+            );
+            assert!(contract.ping_proposal(proposal_id, None));
+            contract.vote_on_proposal(proposal_id, Vote::Yes);
+            let proposal = contract.get_proposal(proposal_id).unwrap();
+            assert_eq!(proposal.yes_stake.0, 10 * (i as u128 + 2));
+            assert_eq!(proposal.num_votes, i as u64 + 2);
+            // 30 of 40 at the snapshot epoch is more than 2/3.
+            assert_eq!(proposal.result.is_some(), i == 1);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "test0 is not a validator")]
+    fn test_vote_without_snapshot_stake() {
+        let validators = (0..2)
+            .map(|i| (format!("test{}", i), 10))
+            .collect::<HashMap<_, _>>();
+        let context = get_context_with_epoch_height("test1".to_string(), 1);
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        let proposal_id = contract.create_proposal("Proposal".to_string(), None, None, Some(1));
+        let context = get_context_with_epoch_height("test0".to_string(), 2);
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        contract.vote_on_proposal(proposal_id, Vote::Yes);
+    }
+
+    #[test]
+    fn test_split_vote() {
+        let validators = HashMap::from_iter(vec![
+            ("pool".to_string(), 60),
+            ("test1".to_string(), 30),
+            ("test2".to_string(), 10),
+        ]);
+        let context = get_context("pool".to_string());
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        let proposal_id = contract.create_proposal(
+            "Proposal".to_string(),
+            None,
+            Some(Threshold {
+                numerator: 1,
+                denominator: 2,
+            }),
+            None,
+        );
+        contract.vote_on_proposal(
+            proposal_id,
+            Vote::Split {
+                yes: U128::from(1),
+                no: U128::from(2),
+                abstain: U128::from(0),
+            },
+        );
+        let proposal = contract.get_proposal(proposal_id).unwrap();
+        assert_eq!((proposal.yes_stake.0, proposal.no_stake.0), (20, 40));
+        contract.withdraw_vote(proposal_id);
+        let proposal = contract.get_proposal(proposal_id).unwrap();
+        assert_eq!((proposal.yes_stake.0, proposal.no_stake.0), (0, 0));
+
+        // The split is rounded down for yes.
+        let context = get_context("test2".to_string());
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        contract.vote_on_proposal(
+            proposal_id,
+            Vote::Split {
+                yes: U128::from(1),
+                no: U128::from(2),
+                abstain: U128::from(0),
+            },
+        );
+        let proposal = contract.get_proposal(proposal_id).unwrap();
+        assert_eq!((proposal.yes_stake.0, proposal.no_stake.0), (3, 7));
+
+        // 3 + 45 of 100 is not more than a half.
+        let context = get_context("pool".to_string());
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        contract.vote_on_proposal(
+            proposal_id,
+            Vote::Split {
+                yes: U128::from(3 * 10u128.pow(30)),
+                no: U128::from(10u128.pow(30)),
+                abstain: U128::from(0),
+            },
+        );
+        let proposal = contract.get_proposal(proposal_id).unwrap();
+        assert_eq!((proposal.yes_stake.0, proposal.no_stake.0), (48, 22));
+        assert!(proposal.result.is_none());
+
+        let context = get_context("test1".to_string());
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        contract.vote_on_proposal(proposal_id, Vote::Yes);
+        assert!(contract.get_proposal_result(proposal_id).is_some());
+    }
+
+    #[test]
+    fn test_split_vote_with_abstain() {
+        let validators =
+            HashMap::from_iter(vec![("pool".to_string(), 60), ("test1".to_string(), 10)]);
+        let context = get_context("pool".to_string());
+        testing_env!(
+            context,
+            Default::default(),
+            Default::default(),
+            validators.clone()
+        );
+        let mut contract = VotingContract::new();
+        let split = Vote::Split {
+            yes: U128::from(1),
+            no: U128::from(1),
+            abstain: U128::from(1),
+        };
+        contract.vote_on_proposal(TRANSFERS_PROPOSAL_ID, split);
+        let proposal = contract.get_proposal(TRANSFERS_PROPOSAL_ID).unwrap();
+        assert_eq!(
+            (
+                proposal.yes_stake.0,
+                proposal.no_stake.0,
+                proposal.abstain_stake.0
+            ),
+            (20, 20, 20)
+        );
+
+        // Yes and abstain are rounded down, no takes the rest.
+        let context = get_context("test1".to_string());
+        testing_env!(context, Default::default(), Default::default(), validators);
+        contract.vote_on_proposal(TRANSFERS_PROPOSAL_ID, split);
+        let proposal = contract.get_proposal(TRANSFERS_PROPOSAL_ID).unwrap();
+        assert_eq!(
+            (
+                proposal.yes_stake.0,
+                proposal.no_stake.0,
+                proposal.abstain_stake.0
+            ),
+            (23, 24, 23)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The split vote should have a positive weight")]
+    fn test_split_vote_without_weight() {
+        let validators = HashMap::from_iter(vec![("test1".to_string(), 10)]);
+        let context = get_context("test1".to_string());
+        testing_env!(context, Default::default(), Default::default(), validators);
+        let mut contract = VotingContract::new();
+        contract.vote_on_proposal(
+            TRANSFERS_PROPOSAL_ID,
+            Vote::Split {
+                yes: U128::from(0),
+                no: U128::from(0),
+                abstain: U128::from(0),
+            },
+        );
+    }
+
+    #[test]
+    fn test_threshold_large_stake() {
+        let threshold = Threshold {
+            numerator: 2,
+            denominator: 3,
+        };
+        let total_stake: Balance = 3 * 10u128.pow(36);
+        assert!(!threshold.is_exceeded_by(2 * 10u128.pow(36), total_stake));
+        assert!(threshold.is_exceeded_by(2 * 10u128.pow(36) + 1, total_stake));
+    }
 }
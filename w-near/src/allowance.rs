@@ -0,0 +1,119 @@
+use crate::events::FtTransfer;
+use crate::*;
+use near_sdk::assert_one_yocto;
+use near_sdk::collections::LookupMap;
+
+/// Prefix of the allowances map from `(owner_id, spender_id)` to the amount of wNEAR the spender
+/// can still transfer from the owner. It's kept outside of the `Contract` struct, so the state of
+/// already deployed contracts remains compatible.
+const ALLOWANCES_PREFIX: &[u8] = b"p";
+
+fn allowances() -> LookupMap<(AccountId, AccountId), Balance> {
+    LookupMap::new(ALLOWANCES_PREFIX.to_vec())
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Allows `spender_id` to transfer up to `amount` of wNEAR from the predecessor account with
+    /// `ft_transfer_from`. Replaces the previous allowance, `0` removes it.
+    /// The attached deposit pays for the storage of the allowance, the rest of it is refunded.
+    /// Removing the allowance refunds its storage.
+    /// Requirements:
+    /// * The predecessor account should be registered.
+    /// * Requires attached deposit of at least 1 yoctoNEAR.
+    #[payable]
+    pub fn ft_approve(&mut self, spender_id: ValidAccountId, amount: U128) {
+        let attached_deposit = env::attached_deposit();
+        assert!(
+            attached_deposit > 0,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let owner_id = env::predecessor_account_id();
+        self.ft.internal_unwrap_balance_of(&owner_id);
+        assert_ne!(
+            &owner_id,
+            spender_id.as_ref(),
+            "The owner can't approve itself"
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        let mut allowances = allowances();
+        let key = (owner_id.clone(), spender_id.into());
+        if amount.0 > 0 {
+            allowances.insert(&key, &amount.0);
+        } else {
+            allowances.remove(&key);
+        }
+        let storage_usage = env::storage_usage();
+        let refund = if storage_usage >= initial_storage_usage {
+            let storage_cost =
+                Balance::from(storage_usage - initial_storage_usage) * env::storage_byte_cost();
+            assert!(
+                attached_deposit >= storage_cost,
+                "Requires attached deposit of at least {} yoctoNEAR",
+                storage_cost
+            );
+            attached_deposit - storage_cost
+        } else {
+            attached_deposit
+                + Balance::from(initial_storage_usage - storage_usage) * env::storage_byte_cost()
+        };
+        if refund > 0 {
+            Promise::new(owner_id).transfer(refund);
+        }
+    }
+
+    /// Transfers `amount` of wNEAR from `owner_id` to `receiver_id` on behalf of the owner.
+    /// Once the allowance is used up, it's removed and its storage is refunded to the owner.
+    /// Requirements:
+    /// * The owner should have approved at least the `amount` to the predecessor account.
+    /// * The receiver account should be registered.
+    /// * Requires attached deposit of exactly 1 yoctoNEAR.
+    #[payable]
+    pub fn ft_transfer_from(
+        &mut self,
+        owner_id: ValidAccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let mut allowances = allowances();
+        let key = (owner_id.clone().into(), env::predecessor_account_id());
+        let allowance = allowances.get(&key).unwrap_or(0);
+        assert!(amount.0 <= allowance, "The allowance is not enough");
+        if amount.0 < allowance {
+            allowances.insert(&key, &(allowance - amount.0));
+        } else {
+            // The allowance is used up, so it's removed and its storage is refunded to the owner.
+            let initial_storage_usage = env::storage_usage();
+            allowances.remove(&key);
+            let refund = Balance::from(initial_storage_usage - env::storage_usage())
+                * env::storage_byte_cost();
+            if refund > 0 {
+                Promise::new(owner_id.clone().into()).transfer(refund);
+            }
+        }
+        self.ft.internal_transfer(
+            owner_id.as_ref(),
+            receiver_id.as_ref(),
+            amount.0,
+            memo.clone(),
+        );
+        FtTransfer {
+            old_owner_id: owner_id.as_ref(),
+            new_owner_id: receiver_id.as_ref(),
+            amount,
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    /// Returns the amount of wNEAR `spender_id` can still transfer from `owner_id`.
+    pub fn ft_allowance(&self, owner_id: ValidAccountId, spender_id: ValidAccountId) -> U128 {
+        allowances()
+            .get(&(owner_id.into(), spender_id.into()))
+            .unwrap_or(0)
+            .into()
+    }
+}
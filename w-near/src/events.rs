@@ -0,0 +1,72 @@
+//! NEP-141 events in the NEP-297 format, so indexers can tell wrapping and unwrapping (mint and
+//! burn) from ordinary transfers.
+//!
+//! Events are logged as `EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":..,"data":[..]}`.
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json::json;
+use near_sdk::{log, AccountId};
+
+const FT_STANDARD_NAME: &str = "nep141";
+const FT_STANDARD_VERSION: &str = "1.0.0";
+
+/// Tokens minted to `owner_id`.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtMint<'a> {
+    pub owner_id: &'a AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl FtMint<'_> {
+    pub fn emit(&self) {
+        emit_event("ft_mint", &[self]);
+    }
+}
+
+/// Tokens burned from `owner_id`.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtBurn<'a> {
+    pub owner_id: &'a AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl FtBurn<'_> {
+    pub fn emit(&self) {
+        emit_event("ft_burn", &[self]);
+    }
+}
+
+/// Tokens transferred from `old_owner_id` to `new_owner_id`.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtTransfer<'a> {
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl FtTransfer<'_> {
+    pub fn emit(&self) {
+        emit_event("ft_transfer", &[self]);
+    }
+}
+
+fn emit_event<T: Serialize>(event: &str, data: &[T]) {
+    log!(
+        "EVENT_JSON:{}",
+        json!({
+            "standard": FT_STANDARD_NAME,
+            "version": FT_STANDARD_VERSION,
+            "event": event,
+            "data": data,
+        })
+    );
+}
@@ -4,22 +4,35 @@
 * The aim of the contract is to enable the wrapping of the native NEAR token into a NEP-141 compatible token.
 * It supports methods `near_deposit` and `near_withdraw` that wraps and unwraps NEAR tokens.
 * They are effectively mint and burn underlying wNEAR tokens.
+* `near_deposit_and_call` wraps NEAR and transfers it with `ft_transfer_call` flow in one step.
 *
 * lib.rs is the main entry point.
 * w_near.rs contains interfaces for depositing and withdrawing
+* events.rs contains NEP-297 events for minting, burning and transferring tokens
+* metadata.rs contains the owner role and the owner-updatable token metadata
+* allowance.rs contains interfaces for approving and transferring tokens on behalf of the owner
 */
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
 use near_contract_standards::fungible_token::metadata::{
     FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
 };
+use near_contract_standards::fungible_token::resolver::FungibleTokenResolver;
 use near_contract_standards::fungible_token::FungibleToken;
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::{ValidAccountId, U128};
-// Needed by `impl_fungible_token_core` for old Rust.
-#[allow(unused_imports)]
-use near_sdk::env;
-use near_sdk::{near_bindgen, AccountId, PanicOnDefault, PromiseOrValue};
+use near_sdk::{
+    env, log, near_bindgen, AccountId, Balance, PanicOnDefault, Promise, PromiseOrValue,
+};
+
+use crate::events::{FtBurn, FtTransfer};
 
+mod allowance;
+mod events;
 mod legacy_storage;
+mod metadata;
 mod w_near;
 
 near_sdk::setup_alloc!();
@@ -33,27 +46,174 @@ pub struct Contract {
 #[near_bindgen]
 impl Contract {
     #[init]
-    pub fn new() -> Self {
-        Self {
+    pub fn new(owner_id: ValidAccountId) -> Self {
+        let mut this = Self {
             ft: FungibleToken::new(b"a".to_vec()),
+        };
+        this.internal_set_owner_id(owner_id.as_ref());
+        this
+    }
+
+    /// Sets the owner of the contract deployed before the owner role was added.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate(owner_id: ValidAccountId) -> Self {
+        let mut this: Self = env::state_read().expect("ERR_NOT_INITIALIZED");
+        assert!(!this.is_owner_id_set(), "ERR_ALREADY_MIGRATED");
+        this.internal_set_owner_id(owner_id.as_ref());
+        this
+    }
+}
+
+impl Contract {
+    /// Called when the account is unregistered. With `force` the account may still have wNEAR,
+    /// which is burned, so the NEAR behind it is sent to the account.
+    fn on_account_closed(&mut self, account_id: AccountId, balance: Balance) {
+        if balance > 0 {
+            log!("Closed @{} with {} wNEAR", account_id, balance);
+            FtBurn {
+                owner_id: &account_id,
+                amount: balance.into(),
+                memo: Some("force close"),
+            }
+            .emit();
+            Promise::new(account_id).transfer(balance);
         }
     }
 }
 
-near_contract_standards::impl_fungible_token_core!(Contract, ft);
-near_contract_standards::impl_fungible_token_storage!(Contract, ft);
+#[near_bindgen]
+impl FungibleTokenCore for Contract {
+    #[payable]
+    fn ft_transfer(&mut self, receiver_id: ValidAccountId, amount: U128, memo: Option<String>) {
+        self.ft
+            .ft_transfer(receiver_id.clone(), amount, memo.clone());
+        FtTransfer {
+            old_owner_id: &env::predecessor_account_id(),
+            new_owner_id: receiver_id.as_ref(),
+            amount,
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let result = self
+            .ft
+            .ft_transfer_call(receiver_id.clone(), amount, memo.clone(), msg);
+        FtTransfer {
+            old_owner_id: &env::predecessor_account_id(),
+            new_owner_id: receiver_id.as_ref(),
+            amount,
+            memo: memo.as_deref(),
+        }
+        .emit();
+        result
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.ft.ft_total_supply()
+    }
+
+    fn ft_balance_of(&self, account_id: ValidAccountId) -> U128 {
+        self.ft.ft_balance_of(account_id)
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenResolver for Contract {
+    /// Resolves `ft_transfer_call`. See `internal_resolve_transfer`.
+    #[private]
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+    ) -> U128 {
+        let (used_amount, _) =
+            self.internal_resolve_transfer(&sender_id.into(), receiver_id, amount);
+        used_amount.into()
+    }
+}
 
+impl Contract {
+    /// Resolves the transfer call and reports tokens that the receiver refunded. If the sender
+    /// unregistered in the meantime, the refund is burned and the NEAR behind it is sent to the
+    /// sender.
+    /// Returns the amount used by the receiver and the amount refunded to the sender's wNEAR.
+    pub(crate) fn internal_resolve_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+    ) -> (Balance, Balance) {
+        let (used_amount, burned_amount) =
+            self.ft
+                .internal_ft_resolve_transfer(sender_id, receiver_id.clone(), amount);
+        if burned_amount > 0 {
+            FtBurn {
+                owner_id: receiver_id.as_ref(),
+                amount: burned_amount.into(),
+                memo: Some("refund"),
+            }
+            .emit();
+            Promise::new(sender_id.clone()).transfer(burned_amount);
+            return (used_amount, 0);
+        }
+        let refunded_amount = amount.0 - used_amount;
+        if refunded_amount > 0 {
+            FtTransfer {
+                old_owner_id: receiver_id.as_ref(),
+                new_owner_id: sender_id,
+                amount: refunded_amount.into(),
+                memo: Some("refund"),
+            }
+            .emit();
+        }
+        (used_amount, refunded_amount)
+    }
+}
+
+// `impl_fungible_token_storage!` can't pass the closed account to `on_account_closed` due to the
+// macro hygiene, so the storage management is implemented here.
 #[near_bindgen]
-impl FungibleTokenMetadataProvider for Contract {
-    fn ft_metadata(&self) -> FungibleTokenMetadata {
-        FungibleTokenMetadata {
-            spec: FT_METADATA_SPEC.to_string(),
-            name: String::from("Wrapped NEAR fungible token"),
-            symbol: String::from("wNEAR"),
-            icon: None,
-            reference: None,
-            reference_hash: None,
-            decimals: 24,
+impl StorageManagement for Contract {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<ValidAccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.ft.storage_deposit(account_id, registration_only)
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.ft.storage_withdraw(amount)
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        if let Some((account_id, balance)) = self.ft.internal_storage_unregister(force) {
+            self.on_account_closed(account_id, balance);
+            true
+        } else {
+            false
         }
     }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        self.ft.storage_balance_bounds()
+    }
+
+    fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance> {
+        self.ft.storage_balance_of(account_id)
+    }
 }
@@ -0,0 +1,86 @@
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+/// Storage key of the owner account ID. It's kept outside of the `Contract` struct, so the state
+/// of already deployed contracts remains compatible.
+const OWNER_KEY: &[u8] = b"o";
+
+/// Storage key of the metadata set by the owner.
+const METADATA_KEY: &[u8] = b"m";
+
+const DECIMALS: u8 = 24;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the owner of the contract who can update the metadata. It's set by `new` or
+    /// `migrate`.
+    pub fn get_owner_id(&self) -> AccountId {
+        env::storage_read(OWNER_KEY)
+            .map(|value| AccountId::try_from_slice(&value).expect("ERR_OWNER_ID_DESERIALIZE"))
+            .expect("ERR_OWNER_ID_NOT_SET")
+    }
+
+    /// Changes the owner of the contract.
+    /// Requirements:
+    /// * Can only be called by the current owner.
+    /// * Requires attached deposit of exactly 1 yoctoNEAR.
+    #[payable]
+    pub fn set_owner_id(&mut self, owner_id: ValidAccountId) {
+        self.assert_owner();
+        self.internal_set_owner_id(owner_id.as_ref());
+    }
+
+    /// Replaces the token metadata returned by `ft_metadata`.
+    /// Requirements:
+    /// * Can only be called by the owner.
+    /// * Requires attached deposit of exactly 1 yoctoNEAR.
+    /// * The metadata should be valid and keep 24 decimals of NEAR.
+    #[payable]
+    pub fn set_metadata(&mut self, metadata: FungibleTokenMetadata) {
+        self.assert_owner();
+        metadata.assert_valid();
+        assert_eq!(
+            metadata.decimals, DECIMALS,
+            "wNEAR should have the same decimals as NEAR"
+        );
+        env::storage_write(METADATA_KEY, &metadata.try_to_vec().unwrap());
+    }
+}
+
+impl Contract {
+    pub(crate) fn is_owner_id_set(&self) -> bool {
+        env::storage_has_key(OWNER_KEY)
+    }
+
+    pub(crate) fn internal_set_owner_id(&mut self, owner_id: &AccountId) {
+        env::storage_write(OWNER_KEY, &owner_id.try_to_vec().unwrap());
+    }
+
+    fn assert_owner(&self) {
+        assert_one_yocto();
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.get_owner_id(),
+            "Can only be called by the owner"
+        );
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenMetadataProvider for Contract {
+    fn ft_metadata(&self) -> FungibleTokenMetadata {
+        env::storage_read(METADATA_KEY)
+            .map(|value| {
+                FungibleTokenMetadata::try_from_slice(&value).expect("ERR_METADATA_DESERIALIZE")
+            })
+            .unwrap_or_else(|| FungibleTokenMetadata {
+                spec: FT_METADATA_SPEC.to_string(),
+                name: String::from("Wrapped NEAR fungible token"),
+                symbol: String::from("wNEAR"),
+                icon: None,
+                reference: None,
+                reference_hash: None,
+                decimals: DECIMALS,
+            })
+    }
+}
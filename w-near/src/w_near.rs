@@ -1,6 +1,43 @@
+use crate::events::{FtBurn, FtMint, FtTransfer};
 use crate::*;
 use near_sdk::json_types::U128;
-use near_sdk::{assert_one_yocto, env, log, Promise};
+use near_sdk::{assert_one_yocto, env, ext_contract, is_promise_success, log, Gas, Promise};
+
+/// The amount of gas given to complete `near_resolve_withdraw` call.
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = 10_000_000_000_000;
+
+/// The amount of gas given to complete `near_resolve_deposit_and_call` call.
+/// It may unwrap the refund, which includes `near_resolve_withdraw` call.
+const GAS_FOR_RESOLVE_DEPOSIT_AND_CALL: Gas = 15_000_000_000_000 + GAS_FOR_RESOLVE_WITHDRAW;
+
+/// The amount of gas `near_deposit_and_call` keeps for itself and the resolve call. The rest of
+/// the prepaid gas is given to `ft_on_transfer` of the receiver.
+const GAS_FOR_DEPOSIT_AND_CALL: Gas = 25_000_000_000_000 + GAS_FOR_RESOLVE_DEPOSIT_AND_CALL;
+
+const NO_DEPOSIT: Balance = 0;
+
+#[ext_contract(ext_self)]
+pub trait WNearResolver {
+    fn near_resolve_withdraw(&mut self, account_id: AccountId, amount: U128) -> bool;
+
+    fn near_resolve_deposit_and_call(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        unwrap_refund: bool,
+    ) -> U128;
+}
+
+#[ext_contract(ext_fungible_token_receiver)]
+pub trait FungibleTokenReceiver {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
 
 #[near_bindgen]
 impl Contract {
@@ -11,24 +48,108 @@ impl Contract {
     /// * If account is not registered will fail if attached deposit is below registration limit.
     #[payable]
     pub fn near_deposit(&mut self) {
-        let mut amount = env::attached_deposit();
+        let amount = env::attached_deposit();
         assert!(amount > 0, "Requires positive attached deposit");
-        let account_id = env::predecessor_account_id();
-        if !self.ft.accounts.contains_key(&account_id) {
-            // Not registered, register if enough $NEAR has been attached.
-            // Subtract registration amount from the account balance.
-            assert!(
-                amount >= self.ft.storage_balance_bounds().min.0,
-                "ERR_DEPOSIT_TOO_SMALL"
-            );
-            self.ft.internal_register_account(&account_id);
-            amount -= self.ft.storage_balance_bounds().min.0;
+        self.internal_near_deposit(&env::predecessor_account_id(), amount);
+    }
+
+    /// Deposit NEAR to mint wNEAR tokens to the given `receiver_id` account in this contract.
+    /// Requirements:
+    /// * The receiver account doesn't need to be registered. The registration is paid from the
+    ///   attached deposit.
+    /// * Requires positive attached deposit.
+    /// * If receiver is not registered will fail if attached deposit is below registration limit.
+    #[payable]
+    pub fn near_deposit_to(&mut self, receiver_id: ValidAccountId) {
+        let amount = env::attached_deposit();
+        assert!(amount > 0, "Requires positive attached deposit");
+        self.internal_near_deposit(receiver_id.as_ref(), amount);
+    }
+
+    /// Deposit NEAR to mint wNEAR tokens to the predecessor account and transfer them to
+    /// `receiver_id` with `ft_transfer_call` flow: calls `ft_on_transfer` on the receiver and
+    /// refunds unused tokens to the predecessor.
+    /// If `unwrap_refund` is true, the refunded tokens are unwrapped and sent back as NEAR.
+    /// Requirements:
+    /// * The predecessor account doesn't need to be registered. The registration is paid from the
+    ///   attached deposit.
+    /// * The receiver account should be registered.
+    /// * Requires attached deposit above the registration limit if the predecessor is not
+    ///   registered.
+    /// Returns the amount of wNEAR used by the receiver.
+    #[payable]
+    pub fn near_deposit_and_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        memo: Option<String>,
+        msg: String,
+        unwrap_refund: Option<bool>,
+    ) -> PromiseOrValue<U128> {
+        assert!(
+            env::prepaid_gas() > GAS_FOR_DEPOSIT_AND_CALL,
+            "More gas is required"
+        );
+        let sender_id = env::predecessor_account_id();
+        let amount = self.internal_near_deposit(&sender_id, env::attached_deposit());
+        self.ft
+            .internal_transfer(&sender_id, receiver_id.as_ref(), amount, memo.clone());
+        FtTransfer {
+            old_owner_id: &sender_id,
+            new_owner_id: receiver_id.as_ref(),
+            amount: amount.into(),
+            memo: memo.as_deref(),
         }
-        self.ft.internal_deposit(&account_id, amount);
-        log!("Deposit {} NEAR to {}", amount, account_id);
+        .emit();
+        ext_fungible_token_receiver::ft_on_transfer(
+            sender_id.clone(),
+            amount.into(),
+            msg,
+            receiver_id.as_ref(),
+            NO_DEPOSIT,
+            env::prepaid_gas() - GAS_FOR_DEPOSIT_AND_CALL,
+        )
+        .then(ext_self::near_resolve_deposit_and_call(
+            sender_id,
+            receiver_id.into(),
+            amount.into(),
+            unwrap_refund.unwrap_or(false),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_DEPOSIT_AND_CALL,
+        ))
+        .into()
+    }
+
+    /// Callback after `ft_on_transfer` of `near_deposit_and_call`. Resolves the transfer like
+    /// `ft_resolve_transfer` and unwraps the refund back to NEAR if `unwrap_refund` is true.
+    /// Returns the amount of wNEAR used by the receiver.
+    #[private]
+    pub fn near_resolve_deposit_and_call(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        unwrap_refund: bool,
+    ) -> U128 {
+        let (used_amount, refunded_amount) =
+            self.internal_resolve_transfer(&sender_id, receiver_id, amount);
+        if unwrap_refund && refunded_amount > 0 {
+            self.internal_near_withdraw(&sender_id, refunded_amount);
+            Promise::new(sender_id.clone())
+                .transfer(refunded_amount)
+                .then(ext_self::near_resolve_withdraw(
+                    sender_id,
+                    refunded_amount.into(),
+                    &env::current_account_id(),
+                    NO_DEPOSIT,
+                    GAS_FOR_RESOLVE_WITHDRAW,
+                ));
+        }
+        used_amount.into()
     }
 
     /// Withdraws wNEAR and send NEAR back to the predecessor account.
+    /// If the transfer fails, the wNEAR is minted back to the predecessor account.
     /// Requirements:
     /// * The predecessor account should be registered.
     /// * `amount` must be a positive integer.
@@ -38,10 +159,122 @@ impl Contract {
     pub fn near_withdraw(&mut self, amount: U128) -> Promise {
         assert_one_yocto();
         let account_id = env::predecessor_account_id();
-        let amount = amount.into();
-        self.ft.internal_withdraw(&account_id, amount);
-        log!("Withdraw {} yoctoNEAR from {}", amount, account_id);
+        self.internal_near_withdraw(&account_id, amount.into());
         // Transferring NEAR and refunding 1 yoctoNEAR.
-        Promise::new(account_id).transfer(amount + 1)
+        Promise::new(account_id.clone())
+            .transfer(amount.0 + 1)
+            .then(ext_self::near_resolve_withdraw(
+                account_id,
+                amount,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_RESOLVE_WITHDRAW,
+            ))
+    }
+
+    /// Withdraws wNEAR of the predecessor account and sends NEAR to the given `receiver_id`.
+    /// If the transfer fails, the wNEAR is minted back to the predecessor account.
+    /// Requirements:
+    /// * The predecessor account should be registered.
+    /// * `amount` must be a positive integer.
+    /// * The predecessor account should have at least the `amount` of wNEAR tokens.
+    /// * Requires attached deposit of exactly 1 yoctoNEAR.
+    #[payable]
+    pub fn near_withdraw_to(&mut self, receiver_id: ValidAccountId, amount: U128) -> Promise {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        self.internal_near_withdraw(&account_id, amount.into());
+        // Transferring NEAR with the attached 1 yoctoNEAR.
+        Promise::new(receiver_id.into())
+            .transfer(amount.0 + 1)
+            .then(ext_self::near_resolve_withdraw(
+                account_id,
+                amount,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_RESOLVE_WITHDRAW,
+            ))
+    }
+
+    /// Callback after the NEAR transfer of the withdrawal. Mints the withdrawn `amount` of wNEAR
+    /// back to `account_id` if the transfer failed. If `account_id` was unregistered in the
+    /// meantime, registers it again and pays the storage from the `amount`. If the `amount` doesn't
+    /// cover the storage, the NEAR stays in the contract.
+    /// Returns `true` if the transfer succeeded.
+    #[private]
+    pub fn near_resolve_withdraw(&mut self, account_id: AccountId, amount: U128) -> bool {
+        let transfer_succeeded = is_promise_success();
+        if !transfer_succeeded {
+            let mut amount: Balance = amount.into();
+            if !self.ft.accounts.contains_key(&account_id) {
+                let storage_cost = self.ft.storage_balance_bounds().min.0;
+                if amount < storage_cost {
+                    log!(
+                        "Transfer of {} yoctoNEAR withdrawn by {} failed, not enough to register it again",
+                        amount,
+                        account_id
+                    );
+                    return transfer_succeeded;
+                }
+                self.ft.internal_register_account(&account_id);
+                amount -= storage_cost;
+            }
+            log!(
+                "Transfer withdrawn by {} failed, minting {} yoctoNEAR back",
+                account_id,
+                amount
+            );
+            self.ft.internal_deposit(&account_id, amount);
+            FtMint {
+                owner_id: &account_id,
+                amount: amount.into(),
+                memo: Some("refund"),
+            }
+            .emit();
+        }
+        transfer_succeeded
+    }
+}
+
+impl Contract {
+    /// Mints wNEAR for the given `amount` of NEAR to `account_id`.
+    /// If the account is not registered, registers it and pays the storage from the `amount`.
+    /// Returns the amount of minted wNEAR.
+    pub(crate) fn internal_near_deposit(
+        &mut self,
+        account_id: &AccountId,
+        mut amount: Balance,
+    ) -> Balance {
+        if !self.ft.accounts.contains_key(account_id) {
+            // Not registered, register if enough $NEAR has been attached.
+            // Subtract registration amount from the account balance.
+            assert!(
+                amount >= self.ft.storage_balance_bounds().min.0,
+                "ERR_DEPOSIT_TOO_SMALL"
+            );
+            self.ft.internal_register_account(account_id);
+            amount -= self.ft.storage_balance_bounds().min.0;
+        }
+        self.ft.internal_deposit(account_id, amount);
+        log!("Deposit {} NEAR to {}", amount, account_id);
+        FtMint {
+            owner_id: account_id,
+            amount: amount.into(),
+            memo: None,
+        }
+        .emit();
+        amount
+    }
+
+    /// Burns the `amount` of wNEAR of `account_id`. The caller sends the NEAR.
+    pub(crate) fn internal_near_withdraw(&mut self, account_id: &AccountId, amount: Balance) {
+        self.ft.internal_withdraw(account_id, amount);
+        log!("Withdraw {} yoctoNEAR from {}", amount, account_id);
+        FtBurn {
+            owner_id: account_id,
+            amount: amount.into(),
+            memo: None,
+        }
+        .emit();
     }
 }
@@ -1,10 +1,16 @@
-use near_sdk::json_types::U128;
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
+use near_contract_standards::fungible_token::metadata::{FungibleTokenMetadata, FT_METADATA_SPEC};
+use near_contract_standards::storage_management::StorageManagement;
+use near_sdk::json_types::{ValidAccountId, U128};
 use near_sdk::serde_json::json;
-use near_sdk::Balance;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, Balance, MockedBlockchain, PromiseResult, VMContext};
 use near_sdk_sim::{
     call, deploy, init_simulator, to_yocto, view, ContractAccount, ExecutionResult, UserAccount,
     DEFAULT_GAS,
 };
+use std::convert::TryFrom;
+use w_near::Contract as WNear;
 use w_near::ContractContract as Contract;
 
 near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
@@ -47,6 +53,38 @@ fn wrap_near(user: &UserAccount, amount: Balance) -> ExecutionResult {
     )
 }
 
+// Replace the mocked blockchain keeping its storage, so the given `promise_result` is returned to
+// the callback.
+fn testing_env_with_promise_result(context: VMContext, promise_result: PromiseResult) {
+    let storage = near_sdk::env::take_blockchain_interface()
+        .unwrap()
+        .as_mut_mocked_blockchain()
+        .unwrap()
+        .take_storage();
+
+    near_sdk::env::set_blockchain_interface(Box::new(MockedBlockchain::new(
+        context,
+        Default::default(),
+        Default::default(),
+        vec![promise_result],
+        storage,
+        Default::default(),
+        None,
+    )));
+}
+
+fn icon_metadata() -> FungibleTokenMetadata {
+    FungibleTokenMetadata {
+        spec: FT_METADATA_SPEC.to_string(),
+        name: String::from("Wrapped NEAR fungible token"),
+        symbol: String::from("wNEAR"),
+        icon: Some(String::from("data:image/svg+xml,<svg></svg>")),
+        reference: None,
+        reference_hash: None,
+        decimals: 24,
+    }
+}
+
 fn deploy_legacy() -> (UserAccount, ContractAccount<Contract>) {
     let root = init_simulator(None);
     let w_near = deploy!(
@@ -54,7 +92,7 @@ fn deploy_legacy() -> (UserAccount, ContractAccount<Contract>) {
         contract_id: CONTRACT_ID.to_string(),
         bytes: &LEGACY_W_NEAR_WASM_BYTES,
         signer_account: root,
-        init_method: new()
+        init_method: new(root.valid_account_id())
     );
     (root, w_near)
 }
@@ -66,7 +104,7 @@ fn deploy_w_near() -> (UserAccount, ContractAccount<Contract>) {
         contract_id: CONTRACT_ID.to_string(),
         bytes: &W_NEAR_WASM_BYTES,
         signer_account: root,
-        init_method: new()
+        init_method: new(root.valid_account_id())
     );
     (root, w_near)
 }
@@ -93,6 +131,10 @@ pub fn test_upgrade() {
         .deploy_contract(W_NEAR_WASM_BYTES.to_vec())
         .submit()
         .assert_success();
+    call!(w_near.user_account, w_near.migrate(root.valid_account_id())).assert_success();
+
+    let owner_id: String = view!(w_near.get_owner_id()).unwrap_json();
+    assert_eq!(owner_id, root.account_id());
 
     let storage_minimum_balance: U128 = view!(w_near.storage_minimum_balance()).unwrap_json();
     assert_eq!(storage_minimum_balance.0, STORAGE_BALANCE);
@@ -192,3 +234,406 @@ pub fn test_wrap_without_storage_deposit() {
     let alice_balance: U128 = view!(w_near.ft_balance_of(alice.valid_account_id())).unwrap_json();
     assert_eq!(alice_balance.0, to_yocto("10") - STORAGE_BALANCE);
 }
+
+fn has_event(result: &ExecutionResult, event: &str) -> bool {
+    result.logs().iter().any(|log| {
+        log.starts_with("EVENT_JSON:") && log.contains(&format!("\"event\":\"{}\"", event))
+    })
+}
+
+#[test]
+pub fn test_events() {
+    let (root, w_near) = deploy_w_near();
+
+    let alice = root.create_user("alice".to_string(), to_yocto("100"));
+    legacy_register_user(&alice);
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    legacy_register_user(&bob);
+
+    let result = wrap_near(&alice, to_yocto("10"));
+    result.assert_success();
+    assert!(has_event(&result, "ft_mint"));
+
+    let result = call!(
+        alice,
+        w_near.ft_transfer(bob.valid_account_id(), to_yocto("5").into(), None),
+        deposit = 1
+    );
+    result.assert_success();
+    assert!(has_event(&result, "ft_transfer"));
+    assert!(!has_event(&result, "ft_mint"));
+
+    let result = call!(
+        alice,
+        w_near.near_withdraw(to_yocto("5").into()),
+        deposit = 1
+    );
+    result.assert_success();
+    assert!(has_event(&result, "ft_burn"));
+}
+
+#[test]
+pub fn test_storage_unregister_force() {
+    let (root, w_near) = deploy_w_near();
+
+    let alice = root.create_user("alice".to_string(), to_yocto("100"));
+    legacy_register_user(&alice);
+
+    wrap_near(&alice, to_yocto("10")).assert_success();
+
+    // Can't unregister with positive balance without force.
+    let result = call!(alice, w_near.storage_unregister(None), deposit = 1);
+    assert!(!result.is_ok());
+
+    let balance_before = alice.account().unwrap().amount;
+    let result = call!(alice, w_near.storage_unregister(Some(true)), deposit = 1);
+    result.assert_success();
+    assert!(has_event(&result, "ft_burn"));
+
+    let total_supply: U128 = view!(w_near.ft_total_supply()).unwrap_json();
+    assert_eq!(total_supply.0, 0);
+    // The burned wNEAR and the storage deposit are returned as NEAR.
+    let balance_after = alice.account().unwrap().amount;
+    assert!(balance_after > balance_before + to_yocto("10"));
+}
+
+#[test]
+pub fn test_near_deposit_to() {
+    let (root, w_near) = deploy_w_near();
+
+    let alice = root.create_user("alice".to_string(), to_yocto("100"));
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+
+    // Bob is registered with the attached deposit.
+    call!(
+        alice,
+        w_near.near_deposit_to(bob.valid_account_id()),
+        deposit = to_yocto("10")
+    )
+    .assert_success();
+
+    let bob_balance: U128 = view!(w_near.ft_balance_of(bob.valid_account_id())).unwrap_json();
+    assert_eq!(bob_balance.0, to_yocto("10") - STORAGE_BALANCE);
+    let alice_balance: U128 = view!(w_near.ft_balance_of(alice.valid_account_id())).unwrap_json();
+    assert_eq!(alice_balance.0, 0);
+
+    call!(
+        alice,
+        w_near.near_deposit_to(bob.valid_account_id()),
+        deposit = to_yocto("5")
+    )
+    .assert_success();
+
+    let bob_balance: U128 = view!(w_near.ft_balance_of(bob.valid_account_id())).unwrap_json();
+    assert_eq!(bob_balance.0, to_yocto("15") - STORAGE_BALANCE);
+}
+
+#[test]
+pub fn test_near_withdraw_to() {
+    let (root, w_near) = deploy_w_near();
+
+    let alice = root.create_user("alice".to_string(), to_yocto("100"));
+    legacy_register_user(&alice);
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+
+    wrap_near(&alice, to_yocto("10")).assert_success();
+
+    let bob_near_balance = bob.account().unwrap().amount;
+    call!(
+        alice,
+        w_near.near_withdraw_to(bob.valid_account_id(), to_yocto("4").into()),
+        deposit = 1
+    )
+    .assert_success();
+
+    let alice_balance: U128 = view!(w_near.ft_balance_of(alice.valid_account_id())).unwrap_json();
+    assert_eq!(alice_balance.0, to_yocto("6"));
+    assert_eq!(
+        bob.account().unwrap().amount,
+        bob_near_balance + to_yocto("4") + 1
+    );
+}
+
+#[test]
+pub fn test_near_withdraw_to_failed_transfer() {
+    let (root, w_near) = deploy_w_near();
+
+    let alice = root.create_user("alice".to_string(), to_yocto("100"));
+    legacy_register_user(&alice);
+
+    wrap_near(&alice, to_yocto("10")).assert_success();
+
+    // The receiver account doesn't exist, so the transfer fails and wNEAR is minted back.
+    let result = call!(
+        alice,
+        w_near.near_withdraw_to(
+            ValidAccountId::try_from("nonexistent").unwrap(),
+            to_yocto("4").into()
+        ),
+        deposit = 1
+    );
+    result.assert_success();
+    assert!(result
+        .promise_results()
+        .iter()
+        .any(|result| result.as_ref().map_or(false, |result| !result.is_ok())));
+
+    let alice_balance: U128 = view!(w_near.ft_balance_of(alice.valid_account_id())).unwrap_json();
+    assert_eq!(alice_balance.0, to_yocto("10"));
+    let total_supply: U128 = view!(w_near.ft_total_supply()).unwrap_json();
+    assert_eq!(total_supply.0, to_yocto("10"));
+}
+
+#[test]
+pub fn test_near_deposit_and_call_refund() {
+    let (root, w_near) = deploy_w_near();
+
+    let alice = root.create_user("alice".to_string(), to_yocto("100"));
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    legacy_register_user(&bob);
+
+    // Bob doesn't have a contract, so `ft_on_transfer` fails and all tokens are refunded.
+    call!(
+        alice,
+        w_near.near_deposit_and_call(bob.valid_account_id(), None, "".to_string(), None),
+        deposit = to_yocto("10")
+    )
+    .assert_success();
+
+    let alice_balance: U128 = view!(w_near.ft_balance_of(alice.valid_account_id())).unwrap_json();
+    assert_eq!(alice_balance.0, to_yocto("10") - STORAGE_BALANCE);
+    let bob_balance: U128 = view!(w_near.ft_balance_of(bob.valid_account_id())).unwrap_json();
+    assert_eq!(bob_balance.0, 0);
+
+    // The refund is unwrapped back to NEAR.
+    let alice_near_balance = alice.account().unwrap().amount;
+    call!(
+        alice,
+        w_near.near_deposit_and_call(bob.valid_account_id(), None, "".to_string(), Some(true)),
+        deposit = to_yocto("5")
+    )
+    .assert_success();
+
+    let alice_balance: U128 = view!(w_near.ft_balance_of(alice.valid_account_id())).unwrap_json();
+    assert_eq!(alice_balance.0, to_yocto("10") - STORAGE_BALANCE);
+    let total_supply: U128 = view!(w_near.ft_total_supply()).unwrap_json();
+    assert_eq!(total_supply.0, to_yocto("10") - STORAGE_BALANCE);
+    // Only gas is spent.
+    assert!(alice.account().unwrap().amount > alice_near_balance - to_yocto("1"));
+}
+
+#[test]
+pub fn test_near_withdraw_failed_transfer() {
+    let contract_id = ValidAccountId::try_from(CONTRACT_ID).unwrap();
+    let alice = accounts(0);
+    let mut context = VMContextBuilder::new();
+    context
+        .current_account_id(contract_id.clone())
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(to_yocto("10"));
+    testing_env!(context.build());
+
+    let mut contract = WNear::new(accounts(1));
+    contract.near_deposit();
+    let balance = contract.ft_balance_of(alice.clone());
+
+    testing_env!(context.attached_deposit(1).build());
+    contract.near_withdraw(to_yocto("4").into());
+    assert_eq!(
+        contract.ft_balance_of(alice.clone()).0,
+        balance.0 - to_yocto("4")
+    );
+
+    // Simulate the NEAR transfer to alice failing, the callback mints the wNEAR back.
+    testing_env_with_promise_result(
+        context
+            .predecessor_account_id(contract_id)
+            .attached_deposit(0)
+            .build(),
+        PromiseResult::Failed,
+    );
+    assert!(!contract.near_resolve_withdraw(alice.clone().into(), to_yocto("4").into()));
+    assert_eq!(contract.ft_balance_of(alice).0, balance.0);
+    assert_eq!(contract.ft_total_supply().0, balance.0);
+}
+
+#[test]
+pub fn test_near_withdraw_failed_transfer_unregistered() {
+    let contract_id = ValidAccountId::try_from(CONTRACT_ID).unwrap();
+    let alice = accounts(0);
+    let mut context = VMContextBuilder::new();
+    context
+        .current_account_id(contract_id.clone())
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(to_yocto("10"));
+    testing_env!(context.build());
+
+    let mut contract = WNear::new(accounts(1));
+    contract.near_deposit();
+    let balance = contract.ft_balance_of(alice.clone());
+
+    testing_env!(context.attached_deposit(1).build());
+    contract.near_withdraw(balance);
+    testing_env!(context.attached_deposit(1).build());
+    assert!(contract.storage_unregister(None));
+
+    // Simulate the NEAR transfer to alice failing after alice was unregistered, the callback
+    // registers alice again and mints the rest of the withdrawn amount back.
+    testing_env_with_promise_result(
+        context
+            .predecessor_account_id(contract_id)
+            .attached_deposit(0)
+            .build(),
+        PromiseResult::Failed,
+    );
+    assert!(!contract.near_resolve_withdraw(alice.clone().into(), balance));
+    let storage_cost = contract.storage_balance_bounds().min.0;
+    assert_eq!(
+        contract.storage_balance_of(alice.clone()).unwrap().total.0,
+        storage_cost
+    );
+    assert_eq!(contract.ft_balance_of(alice).0, balance.0 - storage_cost);
+    assert_eq!(contract.ft_total_supply().0, balance.0 - storage_cost);
+    assert!(near_sdk::test_utils::get_created_receipts().is_empty());
+}
+
+#[test]
+pub fn test_near_withdraw_failed_transfer_unregistered_below_storage_cost() {
+    let contract_id = ValidAccountId::try_from(CONTRACT_ID).unwrap();
+    let alice = accounts(0);
+    let mut context = VMContextBuilder::new();
+    context
+        .current_account_id(contract_id.clone())
+        .predecessor_account_id(alice.clone())
+        .attached_deposit(to_yocto("10"));
+    testing_env!(context.build());
+
+    let mut contract = WNear::new(accounts(1));
+    contract.near_deposit();
+    let storage_cost = contract.storage_balance_bounds().min.0;
+    let balance = contract.ft_balance_of(alice.clone());
+
+    testing_env!(context.attached_deposit(1).build());
+    contract.near_withdraw(balance);
+    testing_env!(context.attached_deposit(1).build());
+    assert!(contract.storage_unregister(None));
+
+    // The withdrawn amount doesn't cover the registration, so alice stays unregistered and
+    // nothing is sent back.
+    testing_env_with_promise_result(
+        context
+            .predecessor_account_id(contract_id)
+            .attached_deposit(0)
+            .build(),
+        PromiseResult::Failed,
+    );
+    assert!(!contract.near_resolve_withdraw(alice.clone().into(), (storage_cost - 1).into()));
+    assert!(contract.storage_balance_of(alice).is_none());
+    assert_eq!(contract.ft_total_supply().0, 0);
+    assert!(near_sdk::test_utils::get_created_receipts().is_empty());
+}
+
+#[test]
+pub fn test_set_metadata() {
+    let (root, w_near) = deploy_w_near();
+
+    let owner_id: String = view!(w_near.get_owner_id()).unwrap_json();
+    assert_eq!(owner_id, root.account_id());
+
+    // Only the owner can set the metadata.
+    let alice = root.create_user("alice".to_string(), to_yocto("100"));
+    let result = call!(alice, w_near.set_metadata(icon_metadata()), deposit = 1);
+    assert!(!result.is_ok());
+
+    call!(
+        root,
+        w_near.set_owner_id(alice.valid_account_id()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(alice, w_near.set_metadata(icon_metadata()), deposit = 1).assert_success();
+
+    let metadata: FungibleTokenMetadata = view!(w_near.ft_metadata()).unwrap_json();
+    assert_eq!(
+        metadata.icon,
+        Some(String::from("data:image/svg+xml,<svg></svg>"))
+    );
+}
+
+#[test]
+pub fn test_allowance() {
+    let (root, w_near) = deploy_w_near();
+
+    let alice = root.create_user("alice".to_string(), to_yocto("100"));
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    let router = root.create_user("router".to_string(), to_yocto("100"));
+    legacy_register_user(&alice);
+    legacy_register_user(&bob);
+
+    wrap_near(&alice, to_yocto("10")).assert_success();
+
+    call!(
+        alice,
+        w_near.ft_approve(router.valid_account_id(), to_yocto("4").into()),
+        deposit = to_yocto("0.01")
+    )
+    .assert_success();
+    let allowance: U128 =
+        view!(w_near.ft_allowance(alice.valid_account_id(), router.valid_account_id()))
+            .unwrap_json();
+    assert_eq!(allowance.0, to_yocto("4"));
+
+    call!(
+        router,
+        w_near.ft_transfer_from(
+            alice.valid_account_id(),
+            bob.valid_account_id(),
+            to_yocto("3").into(),
+            None
+        ),
+        deposit = 1
+    )
+    .assert_success();
+
+    let bob_balance: U128 = view!(w_near.ft_balance_of(bob.valid_account_id())).unwrap_json();
+    assert_eq!(bob_balance.0, to_yocto("3"));
+    let allowance: U128 =
+        view!(w_near.ft_allowance(alice.valid_account_id(), router.valid_account_id()))
+            .unwrap_json();
+    assert_eq!(allowance.0, to_yocto("1"));
+
+    // Exceeds the remaining allowance.
+    let result = call!(
+        router,
+        w_near.ft_transfer_from(
+            alice.valid_account_id(),
+            bob.valid_account_id(),
+            to_yocto("2").into(),
+            None
+        ),
+        deposit = 1
+    );
+    assert!(!result.is_ok());
+
+    let alice_balance: U128 = view!(w_near.ft_balance_of(alice.valid_account_id())).unwrap_json();
+    assert_eq!(alice_balance.0, to_yocto("7"));
+
+    // Using up the allowance removes it and refunds its storage to alice.
+    let alice_near_balance = alice.account().unwrap().amount;
+    call!(
+        router,
+        w_near.ft_transfer_from(
+            alice.valid_account_id(),
+            bob.valid_account_id(),
+            to_yocto("1").into(),
+            None
+        ),
+        deposit = 1
+    )
+    .assert_success();
+    let allowance: U128 =
+        view!(w_near.ft_allowance(alice.valid_account_id(), router.valid_account_id()))
+            .unwrap_json();
+    assert_eq!(allowance.0, 0);
+    assert!(alice.account().unwrap().amount > alice_near_balance);
+}
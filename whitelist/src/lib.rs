@@ -1,6 +1,18 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::LookupSet;
+use near_sdk::collections::{LookupSet, UnorderedMap, Vector};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, near_bindgen, AccountId};
+use std::cmp::min;
+
+/// The maximum length of the reason given when adding or removing an account, in bytes.
+const MAX_REASON_LEN: usize = 256;
+
+/// The maximum number of tags of a staking pool.
+const MAX_NUM_TAGS: usize = 16;
+
+/// The maximum length of a staking pool tag, in bytes.
+const MAX_TAG_LEN: usize = 32;
 
 #[global_allocator]
 static ALLOC: near_sdk::wee_alloc::WeeAlloc = near_sdk::wee_alloc::WeeAlloc::INIT;
@@ -18,6 +30,141 @@ pub struct WhitelistContract {
     /// The whitelist of staking pool factories. Any account from this list can whitelist staking
     /// pools.
     pub factory_whitelist: LookupSet<AccountId>,
+
+    /// Enumerable information about the whitelisted staking pools.
+    /// Staking pools whitelisted before the contract version `0.3.0` are missing until they are
+    /// backfilled by the NEAR Foundation.
+    pub staking_pools: UnorderedMap<AccountId, WhitelistEntry>,
+
+    /// Enumerable information about the whitelisted staking pool factories.
+    /// Factories whitelisted before the contract version `0.3.0` are missing until they are
+    /// backfilled by the NEAR Foundation.
+    pub factories: UnorderedMap<AccountId, WhitelistEntry>,
+
+    /// The history of removals of staking pools from the whitelist.
+    pub staking_pool_removals: Vector<RemovalRecord>,
+
+    /// The history of removals of factories from the factory whitelist.
+    pub factory_removals: Vector<RemovalRecord>,
+
+    /// The account ID proposed by the NEAR Foundation to replace it. The proposed account has to
+    /// accept the role to complete the rotation.
+    pub pending_foundation_account_id: Option<AccountId>,
+
+    /// Delegated admins with their permissions. Admins are managed by the NEAR Foundation.
+    pub admins: UnorderedMap<AccountId, AdminPermissions>,
+
+    /// The delay in nanoseconds between the request to remove a staking pool and the removal.
+    /// Staking pools are removed right away if it's `0`.
+    pub removal_delay: u64,
+
+    /// Scheduled removals of staking pools per staking pool account ID.
+    pub pending_removals: UnorderedMap<AccountId, PendingRemoval>,
+
+    /// The shorter removal delay that replaces `removal_delay` once the current delay has passed.
+    pub pending_removal_delay: Option<PendingRemovalDelay>,
+}
+
+/// State of the contract before the whitelist entries became enumerable.
+#[derive(BorshDeserialize)]
+struct OldWhitelistContract {
+    foundation_account_id: AccountId,
+    whitelist: LookupSet<AccountId>,
+    factory_whitelist: LookupSet<AccountId>,
+}
+
+/// Information about a whitelisted account.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WhitelistEntry {
+    /// The account ID that whitelisted the account: the NEAR Foundation or a whitelisted factory.
+    pub added_by: AccountId,
+    /// The block timestamp when the account was whitelisted.
+    pub added_timestamp: U64,
+    /// The optional reason given for whitelisting.
+    pub reason: Option<String>,
+    /// Sorted categories of the staking pool, e.g. `foundation-run`, `community` or
+    /// `liquid-staking`. Always empty for factories.
+    pub tags: Vec<String>,
+}
+
+/// Represents a whitelisted account with its whitelist entry.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableWhitelistEntry {
+    pub account_id: AccountId,
+    #[serde(flatten)]
+    pub entry: WhitelistEntry,
+}
+
+/// The record of an account removed from the whitelist.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RemovalRecord {
+    pub account_id: AccountId,
+    /// The whitelist entry at the time of removal. Missing for accounts whitelisted before the
+    /// contract version `0.3.0`.
+    pub entry: Option<WhitelistEntry>,
+    /// The account ID that removed the account from the whitelist.
+    pub removed_by: AccountId,
+    /// The block timestamp when the account was removed.
+    pub removed_timestamp: U64,
+    /// The optional reason given for the removal.
+    pub reason: Option<String>,
+}
+
+/// Methods a delegated admin is allowed to call on behalf of the NEAR Foundation.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(default)]
+pub struct AdminPermissions {
+    pub add_staking_pool: bool,
+    /// Also allows to cancel scheduled removals of staking pools.
+    pub remove_staking_pool: bool,
+    pub add_factory: bool,
+    pub remove_factory: bool,
+    pub set_staking_pool_tags: bool,
+}
+
+/// Represents a delegated admin with its permissions.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableAdmin {
+    pub account_id: AccountId,
+    pub permissions: AdminPermissions,
+}
+
+/// The removal of a staking pool that is waiting for the removal delay to pass.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingRemoval {
+    /// The account ID that requested the removal.
+    pub requested_by: AccountId,
+    /// The block timestamp when the removal was requested.
+    pub requested_timestamp: U64,
+    /// The block timestamp after which anyone can complete the removal.
+    pub execute_after: U64,
+    /// The optional reason given for the removal.
+    pub reason: Option<String>,
+}
+
+/// The decrease of the removal delay that is waiting for the current delay to pass.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingRemovalDelay {
+    /// The new delay in nanoseconds.
+    pub removal_delay: U64,
+    /// The block timestamp after which the new delay is used.
+    pub effective_after: U64,
+}
+
+/// Represents a scheduled removal of a staking pool.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadablePendingRemoval {
+    pub account_id: AccountId,
+    #[serde(flatten)]
+    pub removal: PendingRemoval,
 }
 
 impl Default for WhitelistContract {
@@ -36,11 +183,30 @@ impl WhitelistContract {
             env::is_valid_account_id(foundation_account_id.as_bytes()),
             "The NEAR Foundation account ID is invalid"
         );
-        Self {
+        Self::from_parts(
             foundation_account_id,
-            whitelist: LookupSet::new(b"w".to_vec()),
-            factory_whitelist: LookupSet::new(b"f".to_vec()),
-        }
+            LookupSet::new(b"w".to_vec()),
+            LookupSet::new(b"f".to_vec()),
+        )
+    }
+
+    /// Migrates the state of the contract deployed before the whitelist entries became
+    /// enumerable. Can only be called by the contract itself.
+    /// The existing entries keep working, but they are not listed until they are backfilled with
+    /// `backfill_entries`.
+    #[init]
+    pub fn migrate() -> Self {
+        assert_eq!(
+            env::predecessor_account_id(),
+            env::current_account_id(),
+            "Can only be called by the contract itself"
+        );
+        let old: OldWhitelistContract = env::state_read().expect("The contract is not initialized");
+        Self::from_parts(
+            old.foundation_account_id,
+            old.whitelist,
+            old.factory_whitelist,
+        )
     }
 
     /***********/
@@ -56,6 +222,29 @@ impl WhitelistContract {
         self.whitelist.contains(&staking_pool_account_id)
     }
 
+    /// Returns `true` if the given staking pool account ID is whitelisted and has all the given
+    /// tags. Staking pools whitelisted before the entries were recorded have no tags.
+    pub fn is_whitelisted_with_tags(
+        &self,
+        staking_pool_account_id: AccountId,
+        required_tags: Vec<String>,
+    ) -> bool {
+        if !self.is_whitelisted(staking_pool_account_id.clone()) {
+            return false;
+        }
+        if required_tags.is_empty() {
+            return true;
+        }
+        self.staking_pools
+            .get(&staking_pool_account_id)
+            .map(|entry| {
+                required_tags
+                    .iter()
+                    .all(|tag| entry.tags.binary_search(tag).is_ok())
+            })
+            .unwrap_or(false)
+    }
+
     /// Returns `true` if the given factory contract account ID is whitelisted.
     pub fn is_factory_whitelisted(&self, factory_account_id: AccountId) -> bool {
         assert!(
@@ -65,72 +254,461 @@ impl WhitelistContract {
         self.factory_whitelist.contains(&factory_account_id)
     }
 
+    /// Returns the number of the listed whitelisted staking pools.
+    pub fn get_number_of_staking_pools(&self) -> u64 {
+        self.staking_pools.len()
+    }
+
+    /// Returns the whitelisted staking pools with their entries, using pagination.
+    pub fn get_staking_pools(
+        &self,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<HumanReadableWhitelistEntry> {
+        Self::internal_get_entries(&self.staking_pools, from_index, limit)
+    }
+
+    /// Returns the whitelist entry of the given staking pool account ID.
+    /// Returns `None` if the pool is not whitelisted or was whitelisted before the entries were
+    /// recorded.
+    pub fn get_staking_pool(&self, staking_pool_account_id: AccountId) -> Option<WhitelistEntry> {
+        self.staking_pools.get(&staking_pool_account_id)
+    }
+
+    /// Returns the number of the listed whitelisted factories.
+    pub fn get_number_of_factories(&self) -> u64 {
+        self.factories.len()
+    }
+
+    /// Returns the whitelisted factories with their entries, using pagination.
+    pub fn get_factories(&self, from_index: u64, limit: u64) -> Vec<HumanReadableWhitelistEntry> {
+        Self::internal_get_entries(&self.factories, from_index, limit)
+    }
+
+    /// Returns the whitelist entry of the given factory account ID.
+    /// Returns `None` if the factory is not whitelisted or was whitelisted before the entries
+    /// were recorded.
+    pub fn get_factory(&self, factory_account_id: AccountId) -> Option<WhitelistEntry> {
+        self.factories.get(&factory_account_id)
+    }
+
+    /// Returns the number of staking pool removals recorded.
+    pub fn get_number_of_staking_pool_removals(&self) -> u64 {
+        self.staking_pool_removals.len()
+    }
+
+    /// Returns the history of staking pool removals, from the oldest, using pagination.
+    pub fn get_staking_pool_removals(&self, from_index: u64, limit: u64) -> Vec<RemovalRecord> {
+        Self::internal_get_removals(&self.staking_pool_removals, from_index, limit)
+    }
+
+    /// Returns the number of factory removals recorded.
+    pub fn get_number_of_factory_removals(&self) -> u64 {
+        self.factory_removals.len()
+    }
+
+    /// Returns the history of factory removals, from the oldest, using pagination.
+    pub fn get_factory_removals(&self, from_index: u64, limit: u64) -> Vec<RemovalRecord> {
+        Self::internal_get_removals(&self.factory_removals, from_index, limit)
+    }
+
+    /// Returns the account ID of the NEAR Foundation.
+    pub fn get_foundation_account_id(&self) -> AccountId {
+        self.foundation_account_id.clone()
+    }
+
+    /// Returns the account ID proposed to replace the NEAR Foundation account ID, if any.
+    pub fn get_pending_foundation_account_id(&self) -> Option<AccountId> {
+        self.pending_foundation_account_id.clone()
+    }
+
+    /// Returns the delegated admins with their permissions, using pagination.
+    pub fn get_admins(&self, from_index: u64, limit: u64) -> Vec<HumanReadableAdmin> {
+        let keys = self.admins.keys_as_vector();
+        let values = self.admins.values_as_vector();
+        (from_index..min(from_index.saturating_add(limit), keys.len()))
+            .map(|index| HumanReadableAdmin {
+                account_id: keys.get(index).unwrap(),
+                permissions: values.get(index).unwrap(),
+            })
+            .collect()
+    }
+
+    /// Returns the permissions of the given delegated admin account ID, if it's an admin.
+    pub fn get_admin(&self, account_id: AccountId) -> Option<AdminPermissions> {
+        self.admins.get(&account_id)
+    }
+
+    /// Returns the delay in nanoseconds between the request to remove a staking pool and the
+    /// removal.
+    pub fn get_removal_delay(&self) -> U64 {
+        self.internal_removal_delay().into()
+    }
+
+    /// Returns the decrease of the removal delay that doesn't apply yet, if any.
+    pub fn get_pending_removal_delay(&self) -> Option<PendingRemovalDelay> {
+        self.pending_removal_delay
+            .clone()
+            .filter(|pending| env::block_timestamp() < pending.effective_after.0)
+    }
+
+    /// Returns the scheduled removals of staking pools, using pagination.
+    pub fn get_pending_removals(
+        &self,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<HumanReadablePendingRemoval> {
+        let keys = self.pending_removals.keys_as_vector();
+        let values = self.pending_removals.values_as_vector();
+        (from_index..min(from_index.saturating_add(limit), keys.len()))
+            .map(|index| HumanReadablePendingRemoval {
+                account_id: keys.get(index).unwrap(),
+                removal: values.get(index).unwrap(),
+            })
+            .collect()
+    }
+
+    /// Returns the scheduled removal of the given staking pool account ID, if any.
+    pub fn get_pending_removal(
+        &self,
+        staking_pool_account_id: AccountId,
+    ) -> Option<PendingRemoval> {
+        self.pending_removals.get(&staking_pool_account_id)
+    }
+
     /************************/
     /* Factory + Foundation */
     /************************/
 
-    /// Adds the given staking pool account ID to the whitelist.
+    /// Adds the given staking pool account ID to the whitelist with an optional reason.
     /// Returns `true` if the staking pool was not in the whitelist before, `false` otherwise.
-    /// This method can be called either by the NEAR foundation or by a whitelisted factory.
-    pub fn add_staking_pool(&mut self, staking_pool_account_id: AccountId) -> bool {
+    /// This method can be called by the NEAR foundation, by a whitelisted factory or by an admin
+    /// with `add_staking_pool` permission.
+    /// Adding a pool whitelisted before the entries were recorded, records its entry.
+    /// Fails if the removal of the staking pool is scheduled, the removal has to be cancelled
+    /// with `cancel_staking_pool_removal` first.
+    pub fn add_staking_pool(
+        &mut self,
+        staking_pool_account_id: AccountId,
+        reason: Option<String>,
+    ) -> bool {
         assert!(
             env::is_valid_account_id(staking_pool_account_id.as_bytes()),
             "The given account ID is invalid"
         );
-        // Can only be called by a whitelisted factory or by the foundation.
+        // Can only be called by a whitelisted factory, by the foundation or by an admin.
         if !self
             .factory_whitelist
             .contains(&env::predecessor_account_id())
         {
-            self.assert_called_by_foundation();
+            self.assert_has_permission(|permissions| permissions.add_staking_pool);
         }
-        self.whitelist.insert(&staking_pool_account_id)
+        assert!(
+            self.pending_removals
+                .get(&staking_pool_account_id)
+                .is_none(),
+            "The removal of the staking pool is scheduled"
+        );
+        internal_add(
+            &mut self.whitelist,
+            &mut self.staking_pools,
+            &staking_pool_account_id,
+            reason,
+        )
     }
 
-    /**************/
-    /* Foundation */
-    /**************/
+    /**********************/
+    /* Admin + Foundation */
+    /**********************/
 
-    /// Removes the given staking pool account ID from the whitelist.
-    /// Returns `true` if the staking pool was present in the whitelist before, `false` otherwise.
-    /// This method can only be called by the NEAR foundation.
-    pub fn remove_staking_pool(&mut self, staking_pool_account_id: AccountId) -> bool {
-        self.assert_called_by_foundation();
+    /// Removes the given staking pool account ID from the whitelist with an optional reason.
+    /// If the removal delay is set, the removal is scheduled instead and can be completed by
+    /// anyone with `execute_staking_pool_removal` once the delay has passed.
+    /// Returns `true` if the staking pool was present in the whitelist before and it was removed
+    /// or its removal was scheduled, `false` otherwise.
+    /// The removal is recorded in the history.
+    /// This method can be called by the NEAR foundation or by an admin with `remove_staking_pool`
+    /// permission.
+    pub fn remove_staking_pool(
+        &mut self,
+        staking_pool_account_id: AccountId,
+        reason: Option<String>,
+    ) -> bool {
+        self.assert_has_permission(|permissions| permissions.remove_staking_pool);
         assert!(
             env::is_valid_account_id(staking_pool_account_id.as_bytes()),
             "The given account ID is invalid"
         );
-        self.whitelist.remove(&staking_pool_account_id)
+        let removal_delay = self.internal_removal_delay();
+        if removal_delay == 0 {
+            self.pending_removals.remove(&staking_pool_account_id);
+            return internal_remove(
+                &mut self.whitelist,
+                &mut self.staking_pools,
+                &mut self.staking_pool_removals,
+                &staking_pool_account_id,
+                &env::predecessor_account_id(),
+                reason,
+            );
+        }
+        assert_valid_reason(&reason);
+        if !self.whitelist.contains(&staking_pool_account_id)
+            || self
+                .pending_removals
+                .get(&staking_pool_account_id)
+                .is_some()
+        {
+            return false;
+        }
+        self.pending_removals.insert(
+            &staking_pool_account_id,
+            &PendingRemoval {
+                requested_by: env::predecessor_account_id(),
+                requested_timestamp: env::block_timestamp().into(),
+                execute_after: env::block_timestamp().saturating_add(removal_delay).into(),
+                reason,
+            },
+        );
+        true
+    }
+
+    /// Cancels the scheduled removal of the given staking pool account ID.
+    /// Returns `true` if the removal was scheduled, `false` otherwise.
+    /// This method can be called by the NEAR foundation or by an admin with `remove_staking_pool`
+    /// permission.
+    pub fn cancel_staking_pool_removal(&mut self, staking_pool_account_id: AccountId) -> bool {
+        self.assert_has_permission(|permissions| permissions.remove_staking_pool);
+        self.pending_removals
+            .remove(&staking_pool_account_id)
+            .is_some()
+    }
+
+    /// Replaces the tags of the given whitelisted staking pool.
+    /// Staking pools whitelisted before the entries were recorded have to be added again first.
+    /// This method can be called by the NEAR foundation or by an admin with
+    /// `set_staking_pool_tags` permission.
+    pub fn set_staking_pool_tags(&mut self, staking_pool_account_id: AccountId, tags: Vec<String>) {
+        self.assert_has_permission(|permissions| permissions.set_staking_pool_tags);
+        assert!(
+            tags.len() <= MAX_NUM_TAGS,
+            "The staking pool can't have more than {} tags",
+            MAX_NUM_TAGS
+        );
+        for tag in &tags {
+            assert!(
+                !tag.is_empty() && tag.len() <= MAX_TAG_LEN,
+                "The tag should be from 1 to {} bytes long",
+                MAX_TAG_LEN
+            );
+        }
+        let mut entry = self
+            .staking_pools
+            .get(&staking_pool_account_id)
+            .expect("The staking pool is not listed in the whitelist");
+        entry.tags = tags;
+        entry.tags.sort();
+        entry.tags.dedup();
+        self.staking_pools.insert(&staking_pool_account_id, &entry);
     }
 
-    /// Adds the given staking pool factory contract account ID to the factory whitelist.
+    /// Adds the given staking pool factory contract account ID to the factory whitelist with an
+    /// optional reason.
     /// Returns `true` if the factory was not in the whitelist before, `false` otherwise.
-    /// This method can only be called by the NEAR foundation.
-    pub fn add_factory(&mut self, factory_account_id: AccountId) -> bool {
+    /// This method can be called by the NEAR foundation or by an admin with `add_factory`
+    /// permission.
+    pub fn add_factory(&mut self, factory_account_id: AccountId, reason: Option<String>) -> bool {
         assert!(
             env::is_valid_account_id(factory_account_id.as_bytes()),
             "The given account ID is invalid"
         );
-        self.assert_called_by_foundation();
-        self.factory_whitelist.insert(&factory_account_id)
+        self.assert_has_permission(|permissions| permissions.add_factory);
+        internal_add(
+            &mut self.factory_whitelist,
+            &mut self.factories,
+            &factory_account_id,
+            reason,
+        )
     }
 
-    /// Removes the given staking pool factory account ID from the factory whitelist.
+    /// Removes the given staking pool factory account ID from the factory whitelist with an
+    /// optional reason.
     /// Returns `true` if the factory was present in the whitelist before, `false` otherwise.
+    /// The removal is recorded in the history.
+    /// This method can be called by the NEAR foundation or by an admin with `remove_factory`
+    /// permission.
+    pub fn remove_factory(
+        &mut self,
+        factory_account_id: AccountId,
+        reason: Option<String>,
+    ) -> bool {
+        self.assert_has_permission(|permissions| permissions.remove_factory);
+        assert!(
+            env::is_valid_account_id(factory_account_id.as_bytes()),
+            "The given account ID is invalid"
+        );
+        internal_remove(
+            &mut self.factory_whitelist,
+            &mut self.factories,
+            &mut self.factory_removals,
+            &factory_account_id,
+            &env::predecessor_account_id(),
+            reason,
+        )
+    }
+
+    /**************/
+    /* Foundation */
+    /**************/
+
+    /// Proposes the given account ID to replace the NEAR Foundation account ID. The rotation is
+    /// completed when the proposed account calls `accept_foundation_account_id`.
+    /// `None` cancels the pending proposal.
+    /// This method can only be called by the NEAR foundation.
+    pub fn propose_foundation_account_id(&mut self, foundation_account_id: Option<AccountId>) {
+        self.assert_called_by_foundation();
+        if let Some(foundation_account_id) = foundation_account_id.as_ref() {
+            assert!(
+                env::is_valid_account_id(foundation_account_id.as_bytes()),
+                "The NEAR Foundation account ID is invalid"
+            );
+        }
+        self.pending_foundation_account_id = foundation_account_id;
+    }
+
+    /// Adds or updates the delegated admin with the given permissions.
+    /// Admins can't manage other admins, the removal delay or the NEAR Foundation account ID.
     /// This method can only be called by the NEAR foundation.
-    pub fn remove_factory(&mut self, factory_account_id: AccountId) -> bool {
+    pub fn set_admin(&mut self, account_id: AccountId, permissions: AdminPermissions) {
         self.assert_called_by_foundation();
         assert!(
-            env::is_valid_account_id(factory_account_id.as_bytes()),
+            env::is_valid_account_id(account_id.as_bytes()),
             "The given account ID is invalid"
         );
-        self.factory_whitelist.remove(&factory_account_id)
+        self.admins.insert(&account_id, &permissions);
+    }
+
+    /// Removes the given delegated admin.
+    /// Returns `true` if the account was an admin, `false` otherwise.
+    /// This method can only be called by the NEAR foundation.
+    pub fn remove_admin(&mut self, account_id: AccountId) -> bool {
+        self.assert_called_by_foundation();
+        self.admins.remove(&account_id).is_some()
+    }
+
+    /// Sets the delay in nanoseconds between the request to remove a staking pool and the
+    /// removal. `0` removes staking pools right away. Already scheduled removals keep their time.
+    /// A longer delay applies right away, while a shorter one applies only after the current
+    /// delay has passed, so the timelock can't be bypassed by lowering it.
+    /// This method can only be called by the NEAR foundation.
+    pub fn set_removal_delay(&mut self, removal_delay: U64) {
+        self.assert_called_by_foundation();
+        let current_removal_delay = self.internal_removal_delay();
+        if removal_delay.0 >= current_removal_delay {
+            self.removal_delay = removal_delay.into();
+            self.pending_removal_delay = None;
+        } else {
+            self.removal_delay = current_removal_delay;
+            self.pending_removal_delay = Some(PendingRemovalDelay {
+                removal_delay,
+                effective_after: env::block_timestamp()
+                    .saturating_add(current_removal_delay)
+                    .into(),
+            });
+        }
+    }
+
+    /// Records the entries of the given staking pools and factories whitelisted before the
+    /// entries were recorded, so they are listed. Every account ID has to be whitelisted already.
+    /// The entries are recorded as added by the NEAR Foundation at the current block timestamp.
+    /// Accounts that already have an entry are skipped.
+    /// Returns the number of recorded entries.
+    /// This method can only be called by the NEAR foundation.
+    pub fn backfill_entries(
+        &mut self,
+        staking_pool_account_ids: Vec<AccountId>,
+        factory_account_ids: Vec<AccountId>,
+    ) -> u64 {
+        self.assert_called_by_foundation();
+        internal_backfill(
+            &self.whitelist,
+            &mut self.staking_pools,
+            &staking_pool_account_ids,
+        ) + internal_backfill(
+            &self.factory_whitelist,
+            &mut self.factories,
+            &factory_account_ids,
+        )
+    }
+
+    /**********/
+    /* Anyone */
+    /**********/
+
+    /// Completes the rotation of the NEAR Foundation account ID.
+    /// This method can only be called by the account ID proposed by the NEAR foundation.
+    pub fn accept_foundation_account_id(&mut self) {
+        let account_id = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_foundation_account_id.as_ref(),
+            Some(&account_id),
+            "Can only be called by the proposed NEAR Foundation account ID"
+        );
+        self.foundation_account_id = account_id;
+        self.pending_foundation_account_id = None;
     }
 
+    /// Completes the scheduled removal of the given staking pool account ID once the removal
+    /// delay has passed.
+    /// Returns `true` if the staking pool was present in the whitelist, `false` otherwise.
+    /// This method can be called by anyone.
+    pub fn execute_staking_pool_removal(&mut self, staking_pool_account_id: AccountId) -> bool {
+        let removal = self
+            .pending_removals
+            .get(&staking_pool_account_id)
+            .expect("The removal of the staking pool is not scheduled");
+        assert!(
+            env::block_timestamp() >= removal.execute_after.0,
+            "The removal of the staking pool can't be completed before {}",
+            removal.execute_after.0
+        );
+        self.pending_removals.remove(&staking_pool_account_id);
+        internal_remove(
+            &mut self.whitelist,
+            &mut self.staking_pools,
+            &mut self.staking_pool_removals,
+            &staking_pool_account_id,
+            &removal.requested_by,
+            removal.reason,
+        )
+    }
+}
+
+impl WhitelistContract {
     /************/
     /* Internal */
     /************/
 
+    fn from_parts(
+        foundation_account_id: AccountId,
+        whitelist: LookupSet<AccountId>,
+        factory_whitelist: LookupSet<AccountId>,
+    ) -> Self {
+        Self {
+            foundation_account_id,
+            whitelist,
+            factory_whitelist,
+            staking_pools: UnorderedMap::new(b"p".to_vec()),
+            factories: UnorderedMap::new(b"a".to_vec()),
+            staking_pool_removals: Vector::new(b"r".to_vec()),
+            factory_removals: Vector::new(b"q".to_vec()),
+            pending_foundation_account_id: None,
+            admins: UnorderedMap::new(b"d".to_vec()),
+            removal_delay: 0,
+            pending_removals: UnorderedMap::new(b"t".to_vec()),
+            pending_removal_delay: None,
+        }
+    }
+
     /// Internal method to verify the predecessor was the NEAR Foundation account ID.
     fn assert_called_by_foundation(&self) {
         assert_eq!(
@@ -139,6 +717,143 @@ impl WhitelistContract {
             "Can only be called by NEAR Foundation"
         );
     }
+
+    /// Internal method to verify the predecessor was the NEAR Foundation account ID or an admin
+    /// with the required permission.
+    fn assert_has_permission(&self, has_permission: impl Fn(&AdminPermissions) -> bool) {
+        let account_id = env::predecessor_account_id();
+        if account_id != self.foundation_account_id {
+            assert!(
+                self.admins
+                    .get(&account_id)
+                    .map(|permissions| has_permission(&permissions))
+                    .unwrap_or(false),
+                "Can only be called by NEAR Foundation or an admin with the permission"
+            );
+        }
+    }
+
+    /// Returns the removal delay in effect at the current block timestamp.
+    fn internal_removal_delay(&self) -> u64 {
+        match self.pending_removal_delay.as_ref() {
+            Some(pending) if env::block_timestamp() >= pending.effective_after.0 => {
+                pending.removal_delay.0
+            }
+            _ => self.removal_delay,
+        }
+    }
+
+    fn internal_get_entries(
+        entries: &UnorderedMap<AccountId, WhitelistEntry>,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<HumanReadableWhitelistEntry> {
+        let keys = entries.keys_as_vector();
+        let values = entries.values_as_vector();
+        (from_index..min(from_index.saturating_add(limit), keys.len()))
+            .map(|index| HumanReadableWhitelistEntry {
+                account_id: keys.get(index).unwrap(),
+                entry: values.get(index).unwrap(),
+            })
+            .collect()
+    }
+
+    fn internal_get_removals(
+        removals: &Vector<RemovalRecord>,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<RemovalRecord> {
+        (from_index..min(from_index.saturating_add(limit), removals.len()))
+            .map(|index| removals.get(index).unwrap())
+            .collect()
+    }
+}
+
+fn assert_valid_reason(reason: &Option<String>) {
+    if let Some(reason) = reason {
+        assert!(
+            reason.len() <= MAX_REASON_LEN,
+            "The reason can't be longer than {} bytes",
+            MAX_REASON_LEN
+        );
+    }
+}
+
+/// Adds the account to the given whitelist and records its entry if it's not recorded yet.
+fn internal_add(
+    whitelist: &mut LookupSet<AccountId>,
+    entries: &mut UnorderedMap<AccountId, WhitelistEntry>,
+    account_id: &AccountId,
+    reason: Option<String>,
+) -> bool {
+    assert_valid_reason(&reason);
+    let added = whitelist.insert(account_id);
+    if entries.get(account_id).is_none() {
+        internal_record_entry(entries, account_id, reason);
+    }
+    added
+}
+
+/// Records the entries of the accounts that are in the given whitelist, but have no entry yet.
+/// Returns the number of recorded entries.
+fn internal_backfill(
+    whitelist: &LookupSet<AccountId>,
+    entries: &mut UnorderedMap<AccountId, WhitelistEntry>,
+    account_ids: &[AccountId],
+) -> u64 {
+    let mut num_recorded = 0;
+    for account_id in account_ids {
+        assert!(
+            whitelist.contains(account_id),
+            "The account {} is not whitelisted",
+            account_id
+        );
+        if entries.get(account_id).is_none() {
+            internal_record_entry(entries, account_id, None);
+            num_recorded += 1;
+        }
+    }
+    num_recorded
+}
+
+fn internal_record_entry(
+    entries: &mut UnorderedMap<AccountId, WhitelistEntry>,
+    account_id: &AccountId,
+    reason: Option<String>,
+) {
+    entries.insert(
+        account_id,
+        &WhitelistEntry {
+            added_by: env::predecessor_account_id(),
+            added_timestamp: env::block_timestamp().into(),
+            reason,
+            tags: vec![],
+        },
+    );
+}
+
+/// Removes the account from the given whitelist and records the removal in the history.
+fn internal_remove(
+    whitelist: &mut LookupSet<AccountId>,
+    entries: &mut UnorderedMap<AccountId, WhitelistEntry>,
+    removals: &mut Vector<RemovalRecord>,
+    account_id: &AccountId,
+    removed_by: &AccountId,
+    reason: Option<String>,
+) -> bool {
+    assert_valid_reason(&reason);
+    let removed = whitelist.remove(account_id);
+    let entry = entries.remove(account_id);
+    if removed {
+        removals.push(&RemovalRecord {
+            account_id: account_id.clone(),
+            entry,
+            removed_by: removed_by.clone(),
+            removed_timestamp: env::block_timestamp().into(),
+            reason,
+        });
+    }
+    removed
 }
 
 #[cfg(test)]
@@ -167,7 +882,7 @@ mod tests {
         // Adding to whitelist by foundation
         context.is_view = false;
         testing_env!(context.clone());
-        assert!(contract.add_staking_pool(account_pool()));
+        assert!(contract.add_staking_pool(account_pool(), None));
 
         // Checking it's whitelisted now
         context.is_view = true;
@@ -177,7 +892,7 @@ mod tests {
         // Adding again. Should return false
         context.is_view = false;
         testing_env!(context.clone());
-        assert!(!contract.add_staking_pool(account_pool()));
+        assert!(!contract.add_staking_pool(account_pool(), None));
 
         // Checking the pool is still whitelisted
         context.is_view = true;
@@ -187,7 +902,7 @@ mod tests {
         // Removing from the whitelist.
         context.is_view = false;
         testing_env!(context.clone());
-        assert!(contract.remove_staking_pool(account_pool()));
+        assert!(contract.remove_staking_pool(account_pool(), None));
 
         // Checking the pool is not whitelisted anymore
         context.is_view = true;
@@ -197,7 +912,7 @@ mod tests {
         // Removing again from the whitelist, should return false.
         context.is_view = false;
         testing_env!(context.clone());
-        assert!(!contract.remove_staking_pool(account_pool()));
+        assert!(!contract.remove_staking_pool(account_pool(), None));
 
         // Checking the pool is still not whitelisted
         context.is_view = true;
@@ -207,7 +922,7 @@ mod tests {
         // Adding again after it was removed. Should return true
         context.is_view = false;
         testing_env!(context.clone());
-        assert!(contract.add_staking_pool(account_pool()));
+        assert!(contract.add_staking_pool(account_pool(), None));
 
         // Checking the pool is now whitelisted again
         context.is_view = true;
@@ -230,7 +945,7 @@ mod tests {
         context.is_view = false;
         context.predecessor_account_id = account_factory();
         testing_env!(context.clone());
-        assert!(contract.add_staking_pool(account_pool()));
+        assert!(contract.add_staking_pool(account_pool(), None));
     }
 
     #[test]
@@ -248,7 +963,7 @@ mod tests {
         context.is_view = false;
         context.predecessor_account_id = account_factory();
         testing_env!(context.clone());
-        assert!(contract.add_factory(account_factory()));
+        assert!(contract.add_factory(account_factory(), None));
     }
 
     #[test]
@@ -265,12 +980,12 @@ mod tests {
         // Adding factory
         context.is_view = false;
         testing_env!(context.clone());
-        assert!(contract.add_factory(account_factory()));
+        assert!(contract.add_factory(account_factory(), None));
 
         // Trying to remove the pool by the factory.
         context.predecessor_account_id = account_factory();
         testing_env!(context.clone());
-        assert!(contract.remove_staking_pool(account_pool()));
+        assert!(contract.remove_staking_pool(account_pool(), None));
     }
 
     #[test]
@@ -291,7 +1006,7 @@ mod tests {
         // Whitelisting factory
         context.is_view = false;
         testing_env!(context.clone());
-        assert!(contract.add_factory(account_factory()));
+        assert!(contract.add_factory(account_factory(), None));
 
         // Check the factory is whitelisted now
         context.is_view = true;
@@ -304,7 +1019,7 @@ mod tests {
         context.is_view = false;
         context.predecessor_account_id = account_factory();
         testing_env!(context.clone());
-        assert!(contract.add_staking_pool(account_pool()));
+        assert!(contract.add_staking_pool(account_pool(), None));
 
         // Checking it's whitelisted now
         context.is_view = true;
@@ -315,7 +1030,7 @@ mod tests {
         context.is_view = false;
         context.predecessor_account_id = account_near();
         testing_env!(context.clone());
-        assert!(contract.remove_staking_pool(account_pool()));
+        assert!(contract.remove_staking_pool(account_pool(), None));
 
         // Checking the pool is not whitelisted anymore
         context.is_view = true;
@@ -325,11 +1040,551 @@ mod tests {
         // Removing the factory
         context.is_view = false;
         testing_env!(context.clone());
-        assert!(contract.remove_factory(account_factory()));
+        assert!(contract.remove_factory(account_factory(), None));
 
         // Check the factory is not whitelisted anymore
         context.is_view = true;
         testing_env!(context.clone());
         assert!(!contract.is_factory_whitelisted(account_factory()));
     }
+
+    #[test]
+    fn test_whitelist_entries_and_removals() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+
+        // Whitelisting the factory with a reason.
+        context.block_timestamp = 1;
+        testing_env!(context.clone());
+        assert!(contract.add_factory(account_factory(), Some("Audited".to_string())));
+
+        // Whitelisting pools by the foundation and by the factory.
+        context.block_timestamp = 2;
+        testing_env!(context.clone());
+        assert!(contract.add_staking_pool(account_pool(), None));
+        context.predecessor_account_id = account_factory();
+        context.block_timestamp = 3;
+        testing_env!(context.clone());
+        assert!(contract.add_staking_pool("pool2".to_string(), None));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert_eq!(contract.get_number_of_factories(), 1);
+        let factories = contract.get_factories(0, 10);
+        assert_eq!(factories.len(), 1);
+        assert_eq!(factories[0].account_id, account_factory());
+        assert_eq!(factories[0].entry.added_by, account_near());
+        assert_eq!(factories[0].entry.added_timestamp.0, 1);
+        assert_eq!(factories[0].entry.reason, Some("Audited".to_string()));
+
+        assert_eq!(contract.get_number_of_staking_pools(), 2);
+        let pools = contract.get_staking_pools(0, 1);
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].account_id, account_pool());
+        let pools = contract.get_staking_pools(1, 10);
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].account_id, "pool2".to_string());
+        assert_eq!(pools[0].entry.added_by, account_factory());
+        assert_eq!(pools[0].entry.added_timestamp.0, 3);
+        assert!(contract.get_staking_pools(2, 10).is_empty());
+
+        // Removing the pool with a reason.
+        context.is_view = false;
+        context.predecessor_account_id = account_near();
+        context.block_timestamp = 4;
+        testing_env!(context.clone());
+        assert!(contract.remove_staking_pool(account_pool(), Some("Not maintained".to_string())));
+        // Removing again doesn't add to the history.
+        assert!(!contract.remove_staking_pool(account_pool(), None));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert!(contract.get_staking_pool(account_pool()).is_none());
+        assert_eq!(contract.get_number_of_staking_pools(), 1);
+        assert_eq!(contract.get_number_of_staking_pool_removals(), 1);
+        let removals = contract.get_staking_pool_removals(0, 10);
+        assert_eq!(removals.len(), 1);
+        assert_eq!(removals[0].account_id, account_pool());
+        assert_eq!(removals[0].entry.as_ref().unwrap().added_timestamp.0, 2);
+        assert_eq!(removals[0].removed_by, account_near());
+        assert_eq!(removals[0].removed_timestamp.0, 4);
+        assert_eq!(removals[0].reason, Some("Not maintained".to_string()));
+        assert_eq!(contract.get_number_of_factory_removals(), 0);
+
+        // Removing the factory.
+        context.is_view = false;
+        testing_env!(context.clone());
+        assert!(contract.remove_factory(account_factory(), None));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert_eq!(contract.get_number_of_factories(), 0);
+        let removals = contract.get_factory_removals(0, 10);
+        assert_eq!(removals.len(), 1);
+        assert_eq!(removals[0].account_id, account_factory());
+        assert_eq!(
+            removals[0].entry.as_ref().unwrap().reason,
+            Some("Audited".to_string())
+        );
+    }
+
+    #[test]
+    fn test_whitelist_legacy_entry() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        // The pool was whitelisted before the entries were recorded.
+        contract.whitelist.insert(&account_pool());
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert!(contract.is_whitelisted(account_pool()));
+        assert_eq!(contract.get_number_of_staking_pools(), 0);
+
+        // Adding it again records the entry, but returns false.
+        context.is_view = false;
+        testing_env!(context.clone());
+        assert!(!contract.add_staking_pool(account_pool(), None));
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert_eq!(contract.get_number_of_staking_pools(), 1);
+        assert_eq!(
+            contract.get_staking_pool(account_pool()).unwrap().added_by,
+            account_near()
+        );
+    }
+
+    #[test]
+    fn test_backfill_entries() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        // The pool and the factory were whitelisted before the entries were recorded.
+        contract.whitelist.insert(&account_pool());
+        contract.factory_whitelist.insert(&account_factory());
+        assert!(contract.add_staking_pool("pool2".to_string(), Some("Audited".to_string())));
+
+        context.block_timestamp = 10;
+        testing_env!(context.clone());
+        assert_eq!(
+            contract.backfill_entries(
+                vec![account_pool(), "pool2".to_string()],
+                vec![account_factory()]
+            ),
+            2
+        );
+        // Backfilling again doesn't change anything.
+        assert_eq!(
+            contract.backfill_entries(vec![account_pool()], vec![account_factory()]),
+            0
+        );
+
+        context.is_view = true;
+        testing_env!(context);
+        assert_eq!(contract.get_number_of_staking_pools(), 2);
+        assert_eq!(contract.get_number_of_factories(), 1);
+        let entry = contract.get_staking_pool(account_pool()).unwrap();
+        assert_eq!(entry.added_by, account_near());
+        assert_eq!(entry.added_timestamp.0, 10);
+        assert!(entry.reason.is_none());
+        // The existing entry is kept.
+        assert_eq!(
+            contract
+                .get_staking_pool("pool2".to_string())
+                .unwrap()
+                .reason,
+            Some("Audited".to_string())
+        );
+        assert!(contract.get_factory(account_factory()).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "The account pool is not whitelisted")]
+    fn test_backfill_entries_not_whitelisted() {
+        let context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context);
+
+        let mut contract = WhitelistContract::new(account_near());
+        // The factory whitelist doesn't allow to backfill staking pools.
+        contract.factory_whitelist.insert(&account_pool());
+        contract.backfill_entries(vec![account_pool()], vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only be called by NEAR Foundation")]
+    fn test_backfill_entries_not_foundation() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        contract.whitelist.insert(&account_pool());
+
+        context.predecessor_account_id = account_factory();
+        testing_env!(context);
+        contract.backfill_entries(vec![account_pool()], vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "The reason can't be longer than 256 bytes")]
+    fn test_whitelist_reason_too_long() {
+        let context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        contract.add_staking_pool(account_pool(), Some("a".repeat(257)));
+    }
+
+    #[test]
+    fn test_foundation_rotation() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+
+        contract.propose_foundation_account_id(Some(account_dao()));
+        assert_eq!(
+            contract.get_pending_foundation_account_id(),
+            Some(account_dao())
+        );
+        // The current foundation keeps its rights until the rotation is accepted.
+        assert!(contract.add_staking_pool(account_pool(), None));
+
+        context.predecessor_account_id = account_dao();
+        testing_env!(context.clone());
+        contract.accept_foundation_account_id();
+        assert_eq!(contract.get_foundation_account_id(), account_dao());
+        assert_eq!(contract.get_pending_foundation_account_id(), None);
+        assert!(contract.remove_staking_pool(account_pool(), None));
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only be called by the proposed NEAR Foundation account ID")]
+    fn test_foundation_rotation_not_proposed() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        contract.propose_foundation_account_id(Some(account_dao()));
+
+        context.predecessor_account_id = account_factory();
+        testing_env!(context.clone());
+        contract.accept_foundation_account_id();
+    }
+
+    #[test]
+    fn test_admin_permissions() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        contract.set_admin(
+            account_dao(),
+            AdminPermissions {
+                add_staking_pool: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(contract.get_admins(0, 10).len(), 1);
+        assert!(contract.get_admin(account_dao()).unwrap().add_staking_pool);
+
+        // The add-only admin can whitelist pools.
+        context.predecessor_account_id = account_dao();
+        testing_env!(context.clone());
+        assert!(contract.add_staking_pool(account_pool(), None));
+        assert_eq!(
+            contract.get_staking_pool(account_pool()).unwrap().added_by,
+            account_dao()
+        );
+
+        // Removing the admin.
+        context.predecessor_account_id = account_near();
+        testing_env!(context.clone());
+        assert!(contract.remove_admin(account_dao()));
+        assert!(contract.get_admin(account_dao()).is_none());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Can only be called by NEAR Foundation or an admin with the permission"
+    )]
+    fn test_admin_without_permission() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        assert!(contract.add_staking_pool(account_pool(), None));
+        contract.set_admin(
+            account_dao(),
+            AdminPermissions {
+                add_staking_pool: true,
+                ..Default::default()
+            },
+        );
+
+        // The add-only admin can't remove pools.
+        context.predecessor_account_id = account_dao();
+        testing_env!(context.clone());
+        contract.remove_staking_pool(account_pool(), None);
+    }
+
+    #[test]
+    fn test_removal_timelock() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        contract.set_removal_delay(100.into());
+        assert!(contract.add_staking_pool(account_pool(), None));
+
+        // Scheduling the removal, the pool stays whitelisted.
+        context.block_timestamp = 10;
+        testing_env!(context.clone());
+        assert!(contract.remove_staking_pool(account_pool(), Some("Outdated".to_string())));
+        assert!(!contract.remove_staking_pool(account_pool(), None));
+        assert!(contract.is_whitelisted(account_pool()));
+        let removal = contract.get_pending_removal(account_pool()).unwrap();
+        assert_eq!(removal.execute_after.0, 110);
+        assert_eq!(contract.get_pending_removals(0, 10).len(), 1);
+
+        // Cancelling and scheduling again.
+        assert!(contract.cancel_staking_pool_removal(account_pool()));
+        assert!(!contract.cancel_staking_pool_removal(account_pool()));
+        assert!(!contract.add_staking_pool(account_pool(), None));
+        assert!(contract.remove_staking_pool(account_pool(), Some("Outdated".to_string())));
+
+        // Anyone can complete the removal after the delay.
+        context.predecessor_account_id = account_factory();
+        context.block_timestamp = 110;
+        testing_env!(context.clone());
+        assert!(contract.execute_staking_pool_removal(account_pool()));
+        assert!(!contract.is_whitelisted(account_pool()));
+        assert!(contract.get_pending_removal(account_pool()).is_none());
+        let removals = contract.get_staking_pool_removals(0, 10);
+        assert_eq!(removals.len(), 1);
+        assert_eq!(removals[0].removed_by, account_near());
+        assert_eq!(removals[0].removed_timestamp.0, 110);
+        assert_eq!(removals[0].reason, Some("Outdated".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "The removal of the staking pool is scheduled")]
+    fn test_add_staking_pool_during_removal() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        contract.set_removal_delay(100.into());
+        assert!(contract.add_staking_pool(account_pool(), None));
+        assert!(contract.remove_staking_pool(account_pool(), None));
+
+        // Adding the pool again doesn't silently keep the scheduled removal.
+        context.block_timestamp = 10;
+        testing_env!(context);
+        contract.add_staking_pool(account_pool(), None);
+    }
+
+    #[test]
+    fn test_removal_delay_decrease() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        contract.set_removal_delay(100.into());
+        assert_eq!(contract.get_removal_delay().0, 100);
+        assert!(contract.get_pending_removal_delay().is_none());
+        assert!(contract.add_staking_pool(account_pool(), None));
+
+        // Lowering the delay waits out the current one.
+        context.block_timestamp = 10;
+        testing_env!(context.clone());
+        contract.set_removal_delay(0.into());
+        assert_eq!(contract.get_removal_delay().0, 100);
+        assert_eq!(
+            contract
+                .get_pending_removal_delay()
+                .unwrap()
+                .effective_after
+                .0,
+            110
+        );
+        assert!(contract.remove_staking_pool(account_pool(), None));
+        assert!(contract.is_whitelisted(account_pool()));
+        assert_eq!(
+            contract
+                .get_pending_removal(account_pool())
+                .unwrap()
+                .execute_after
+                .0,
+            110
+        );
+        assert!(contract.cancel_staking_pool_removal(account_pool()));
+
+        // The lower delay applies once the previous delay has passed.
+        context.block_timestamp = 110;
+        testing_env!(context.clone());
+        assert_eq!(contract.get_removal_delay().0, 0);
+        assert!(contract.get_pending_removal_delay().is_none());
+        assert!(contract.remove_staking_pool(account_pool(), None));
+        assert!(!contract.is_whitelisted(account_pool()));
+
+        // Raising the delay applies right away.
+        contract.set_removal_delay(50.into());
+        assert_eq!(contract.get_removal_delay().0, 50);
+        assert!(contract.get_pending_removal_delay().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "The removal of the staking pool can't be completed before 110")]
+    fn test_removal_timelock_too_early() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        contract.set_removal_delay(100.into());
+        assert!(contract.add_staking_pool(account_pool(), None));
+        context.block_timestamp = 10;
+        testing_env!(context.clone());
+        assert!(contract.remove_staking_pool(account_pool(), None));
+
+        context.block_timestamp = 109;
+        testing_env!(context.clone());
+        contract.execute_staking_pool_removal(account_pool());
+    }
+
+    #[test]
+    fn test_whitelist_tags() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        assert!(contract.add_staking_pool(account_pool(), None));
+        contract.set_admin(
+            account_dao(),
+            AdminPermissions {
+                set_staking_pool_tags: true,
+                ..Default::default()
+            },
+        );
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert!(contract.is_whitelisted_with_tags(account_pool(), vec![]));
+        assert!(!contract.is_whitelisted_with_tags(account_pool(), vec!["community".to_string()]));
+
+        context.is_view = false;
+        context.predecessor_account_id = account_dao();
+        testing_env!(context.clone());
+        contract.set_staking_pool_tags(
+            account_pool(),
+            vec![
+                "foundation-run".to_string(),
+                "community".to_string(),
+                "community".to_string(),
+            ],
+        );
+
+        context.is_view = true;
+        testing_env!(context.clone());
+        assert_eq!(
+            contract.get_staking_pool(account_pool()).unwrap().tags,
+            vec!["community".to_string(), "foundation-run".to_string()]
+        );
+        assert!(contract.is_whitelisted(account_pool()));
+        assert!(contract.is_whitelisted_with_tags(
+            account_pool(),
+            vec!["foundation-run".to_string(), "community".to_string()]
+        ));
+        assert!(!contract.is_whitelisted_with_tags(
+            account_pool(),
+            vec!["foundation-run".to_string(), "liquid-staking".to_string()]
+        ));
+        assert!(!contract.is_whitelisted_with_tags("pool2".to_string(), vec![]));
+
+        // Tags are kept in the removal history, but the removed pool doesn't match anymore.
+        context.is_view = false;
+        context.predecessor_account_id = account_near();
+        testing_env!(context.clone());
+        assert!(contract.remove_staking_pool(account_pool(), None));
+        assert!(!contract.is_whitelisted_with_tags(account_pool(), vec![]));
+        assert_eq!(
+            contract.get_staking_pool_removals(0, 1)[0]
+                .entry
+                .as_ref()
+                .unwrap()
+                .tags
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Can only be called by NEAR Foundation or an admin with the permission"
+    )]
+    fn test_whitelist_tags_by_factory() {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(account_whitelist())
+            .predecessor_account_id(account_near())
+            .finish();
+        testing_env!(context.clone());
+
+        let mut contract = WhitelistContract::new(account_near());
+        assert!(contract.add_factory(account_factory(), None));
+
+        // Factories can whitelist pools, but can't tag them.
+        context.predecessor_account_id = account_factory();
+        testing_env!(context.clone());
+        assert!(contract.add_staking_pool(account_pool(), None));
+        contract.set_staking_pool_tags(account_pool(), vec!["foundation-run".to_string()]);
+    }
 }
@@ -13,6 +13,9 @@ pub fn account_pool() -> AccountId {
 pub fn account_factory() -> AccountId {
     "factory".to_string()
 }
+pub fn account_dao() -> AccountId {
+    "dao".to_string()
+}
 
 pub struct VMContextBuilder {
     context: VMContext,